@@ -18,6 +18,7 @@ export default function Home() {
   const [lyrics, setLyrics] = useState("");
   const [cardimage, setcardimage] = useState("");
   const [position, setposition] = useState("");
+  const [drawId, setDrawId] = useState(null);
   const [mintdone, setmintdone] = useState(false);
 
   // Creating a new wallet instance
@@ -33,12 +34,11 @@ export default function Home() {
       const contract = new Contract(walletnear.account(), "tarotv0.testnet", {
         changeMethods: ["draw_cards"],
       });
+      // draws a single card from the default deck, the price plus the draw's storage is kept and the rest refunded
       const drawResponse = await contract.draw_cards(
-        {
-          arg_name: "", // argument name and value - pass empty object if no args required
-        },
+        {},
         "30000000000000", // attached GAS (optional)
-        "500000000000000000000000" // attached deposit in yoctoNEAR (optional)
+        "510000000000000000000000" // attached deposit in yoctoNEAR (optional)
       ).then((drawResponse) => {
         console.log("Drawn Card Transaction:", drawResponse);
         // Process drawResponse here if needed
@@ -68,20 +68,16 @@ export default function Home() {
         //   meta: "mintsuccess", // meta information NEAR Wallet will send back to the application. `meta` will be attached to the `callbackUrl` as a url param
         //   args: {
         //     receiver_id: accountId,
-        //     question: description, 
+        //     draw_id: drawId,
         //     reading: lyrics,
-        //     card: drawnCard,
-        //     position: position// argument name and value - pass empty object if no args required
         //   },
         //   gas: 30000000000000, // attached GAS (optional)
         //   amount: 1000000000000000000000000, // attached deposit in yoctoNEAR (optional)
         // }
         {
           receiver_id: accountId,
-          question: description, 
+          draw_id: drawId, // the card and its orientation are read from the stored draw
           reading: lyrics,
-          card: drawnCard,
-          position: position// argument name and value - pass empty object if no args required
         },
         "30000000000000", // attached GAS (optional)
         "1060000000000000000000000" // attached deposit in yoctoNEAR (optional)
@@ -187,6 +183,25 @@ const txHash = searchParams.get('transactionHashes')
 
 // AexLFe9xBrXgEdfediyZU7cNzx6e5RQsxyGQFkkc8Js5
 
+// the cards of a draw are revealed by a callback in a later block, so poll get_draw until they are
+async function getRevealedDraw(drawId) {
+  const { wallet } = await initNear();
+  const contract = new Contract(wallet.account(), "tarotv0.testnet", {
+    viewMethods: ["get_draw"],
+  });
+  for (let attempt = 0; attempt < 20; attempt++) {
+    const draw = await contract.get_draw({ draw_id: drawId });
+    if (!draw) {
+      throw new Error(`Draw ${drawId} not found`);
+    }
+    if (draw.revealed_at) {
+      return draw;
+    }
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+  }
+  throw new Error(`Draw ${drawId} is not revealed yet`);
+}
+
 const accountId = walletnear?.getAccountId();
 useEffect(() => {
   const handleCheck = async () => {
@@ -197,14 +212,22 @@ useEffect(() => {
         const output = JSON.parse(outputString);
         console.log('Transaction Output:', output);
 
-        const card = output[0];
-      const position = output[2];
+      // draw_cards returns the pending draw, anything else (such as a minted token) has no draw_id
+      if (output.draw_id === undefined) {
+        setLoading(false);
+        return;
+      }
+
+      const draw = await getRevealedDraw(output.draw_id);
+      const card = draw.cards[0].card;
+      const position = draw.cards[0].orientation;
 
-      console.log("card", output[0], "position", output[2], "cardimg" , output[1]);
+      console.log("draw", draw.draw_id, "card", card, "position", position, "cardimg", draw.cards[0].card_uri);
 
-      setcardimage(output[1]);
-      setDrawnCard(output[0]);
-      setposition(output[2]);
+      setDrawId(draw.draw_id);
+      setcardimage(draw.cards[0].card_uri);
+      setDrawnCard(card);
+      setposition(position);
 
       if(card)
       {
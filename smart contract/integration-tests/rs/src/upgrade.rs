@@ -0,0 +1,198 @@
+use near_units::parse_near;
+use serde_json::json;
+use workspaces::{Account, Contract};
+
+const NFT_WASM_FILEPATH: &str = "../../res/non_fungible_token.wasm";
+// the first deployment, built from the commit before state versioning, see fixtures/README.md
+const V0_NFT_WASM_FILEPATH: &str = "./fixtures/non_fungible_token_v0.wasm";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // initiate environemnt
+    let worker = workspaces::sandbox().await?;
+
+    // deploy the first deployment, which has no state version
+    let old_wasm = std::fs::read(V0_NFT_WASM_FILEPATH)?;
+    let new_wasm = std::fs::read(NFT_WASM_FILEPATH)?;
+    let nft_contract = worker.dev_deploy(&old_wasm).await?;
+
+    // create accounts
+    let owner = worker.root_account().unwrap();
+    let alice = owner
+        .create_subaccount("alice")
+        .initial_balance(parse_near!("30 N"))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // Initialize contract
+    nft_contract
+        .call("new_default_meta")
+        .args_json(json!({
+            "owner_id": owner.id()
+        }))
+        .transact()
+        .await?;
+
+    // begin tests
+    mint_v0_reading(&alice, &nft_contract, "I The Magician", "reverse").await?;
+    mint_v0_reading(&alice, &nft_contract, "0 The Fool", "upright").await?;
+    test_upgrade(&owner, &alice, &nft_contract, &new_wasm).await?;
+    test_upgrade_by_stranger(&alice, &nft_contract, &new_wasm).await?;
+    test_migrate_by_stranger(&alice, &nft_contract).await?;
+    Ok(())
+}
+
+// mints through the API of the first deployment, which took the card and its orientation as given
+async fn mint_v0_reading(user: &Account, nft_contract: &Contract, card: &str, position: &str) -> anyhow::Result<()> {
+    user.call(nft_contract.id(), "nft_mint")
+        .args_json(json!({
+            "receiver_id": user.id(),
+            "card": card,
+            "reading": "test reading",
+            "_question": "",
+            "position": position,
+        }))
+        .deposit(parse_near!("2 N"))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn mint_reading(user: &Account, nft_contract: &Contract) -> anyhow::Result<()> {
+    let draw: serde_json::Value = user
+        .call(nft_contract.id(), "draw_cards")
+        .args_json(json!({}))
+        .deposit(parse_near!("1 N"))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+
+    user.call(nft_contract.id(), "nft_mint")
+        .args_json(json!({
+            "receiver_id": user.id(),
+            "draw_id": draw["draw_id"],
+            "reading": "test reading",
+        }))
+        .deposit(parse_near!("2 N"))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn test_upgrade_by_stranger(user: &Account, nft_contract: &Contract, wasm: &[u8]) -> anyhow::Result<()> {
+    let outcome = user
+        .call(nft_contract.id(), "upgrade")
+        .args(wasm.to_vec())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(outcome.is_failure());
+
+    println!("      Passed ✅ test_upgrade_by_stranger");
+    Ok(())
+}
+
+async fn test_migrate_by_stranger(user: &Account, nft_contract: &Contract) -> anyhow::Result<()> {
+    let outcome = user
+        .call(nft_contract.id(), "migrate")
+        .args_json(json!({}))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(outcome.is_failure());
+
+    println!("      Passed ✅ test_migrate_by_stranger");
+    Ok(())
+}
+
+async fn test_upgrade(owner: &Account, user: &Account, nft_contract: &Contract, wasm: &[u8]) -> anyhow::Result<()> {
+    let outcome = owner
+        .call(nft_contract.id(), "upgrade")
+        .args(wasm.to_vec())
+        .max_gas()
+        .transact()
+        .await?;
+    // both the deployment and the `migrate` call it schedules must succeed
+    assert!(outcome.is_success(), "{:#?}", outcome);
+    assert!(outcome.receipt_failures().is_empty(), "{:#?}", outcome.receipt_failures());
+
+    let version: String = nft_contract
+        .call("get_state_version")
+        .args_json(json!({}))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(version, "V1");
+
+    let token: serde_json::Value = nft_contract
+        .call("nft_token")
+        .args_json(json!({"token_id": "0"}))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(token["owner_id"], user.id().to_string());
+    assert_eq!(token["metadata"]["description"], "test reading");
+
+    let contract_owner: String = nft_contract
+        .call("get_owner")
+        .args_json(json!({}))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(contract_owner, owner.id().to_string());
+
+    let decks: Vec<(String, String, u64)> = nft_contract
+        .call("get_decks")
+        .args_json(json!({}))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(decks, vec![("celestial".to_string(), "Celestial".to_string(), 22)]);
+
+    let minted: String = nft_contract
+        .call("get_num")
+        .args_json(json!({}))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(minted, "2");
+
+    // the readings of the first deployment get the current orientation names
+    let next: String = owner
+        .call(nft_contract.id(), "migrate_reading_titles")
+        .args_json(json!({"from_token_id": "0", "limit": 10}))
+        .deposit(parse_near!("1 N"))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(next, "2");
+    for (token_id, title) in [("0", "Reading#0: I The Magician in reversed"), ("1", "Reading#1: 0 The Fool in upright")] {
+        let token: serde_json::Value = nft_contract
+            .call("nft_token")
+            .args_json(json!({"token_id": token_id}))
+            .transact()
+            .await?
+            .json()?;
+        assert_eq!(token["metadata"]["title"], title);
+    }
+
+    // the upgraded contract keeps accepting draws, and continues the token ids
+    mint_reading(user, nft_contract).await?;
+    let minted: String = nft_contract
+        .call("get_num")
+        .args_json(json!({}))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(minted, "3");
+
+    println!("      Passed ✅ test_upgrade");
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::Vector;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::events::ReadingAnnotated;
+use crate::{Contract, ContractExt, StorageKey};
+
+/// A note the owner of a reading added after it was minted, such as how things turned out
+/// or a later interpretation. `created_at` is the block timestamp in milliseconds.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Annotation {
+    pub text: String,
+    pub created_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Appends `text` to the annotations of `token_id` and sets its `updated_at` to now.
+    /// Only the token owner can call this. The text is limited like a reading, see `Config`,
+    /// and its storage is paid from the owner's storage balance or the attached deposit.
+    #[payable]
+    pub fn annotate(&mut self, token_id: TokenId, text: String) -> U64 {
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(env::predecessor_account_id(), owner_id, "Only the token owner can annotate it");
+        assert!(!text.is_empty(), "Annotation must not be empty");
+        self.config.assert_reading_len(&text);
+        let initial_storage_usage = env::storage_usage();
+        let created_at = env::block_timestamp_ms();
+        let mut annotations = self.annotations.get(&token_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::AnnotationsPerToken { token_hash: env::sha256(token_id.as_bytes()) })
+        });
+        annotations.push(&Annotation { text, created_at: created_at.into() });
+        self.annotations.insert(&token_id, &annotations);
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            let mut metadata = token_metadata_by_id.get(&token_id).expect("Token metadata not found");
+            metadata.updated_at = Some(created_at.to_string());
+            token_metadata_by_id.insert(&token_id, &metadata);
+        }
+        let index = annotations.len() - 1;
+        self.charge_and_refund(0, initial_storage_usage, "annotation");
+        ReadingAnnotated { owner_id: &owner_id, token_id: &token_id, index: index.into() }.emit();
+        index.into()
+    }
+
+    //view the annotations of a reading, oldest first, paginated like `nft_tokens`
+    pub fn get_annotations(&self, token_id: TokenId, from_index: Option<U128>, limit: Option<u64>) -> Vec<Annotation> {
+        let annotations = match self.annotations.get(&token_id) {
+            Some(annotations) => annotations,
+            None => return Vec::new(),
+        };
+        let start: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(annotations.len() as u128 >= start, "Out of bounds, please use a smaller from_index.");
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert!(limit != 0, "Cannot provide limit of 0.");
+        annotations.iter().skip(start as usize).take(limit).collect()
+    }
+
+    //view the number of annotations of a reading
+    pub fn get_annotation_count(&self, token_id: TokenId) -> U64 {
+        self.annotations.get(&token_id).map(|annotations| annotations.len()).unwrap_or(0).into()
+    }
+}
@@ -0,0 +1,53 @@
+use near_contract_standards::non_fungible_token::approval::ext_nft_approval_receiver;
+use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
+use near_contract_standards::non_fungible_token::{bytes_for_approved_account_id, TokenId};
+use near_sdk::{env, near_bindgen, AccountId, Balance, Gas, Promise};
+
+use crate::{Contract, ContractExt};
+
+const GAS_FOR_NFT_APPROVE: Gas = Gas(10_000_000_000_000);
+
+/// NEP-178 approvals. `nft_approve` pays for a new approval from the owner's storage balance
+/// before the attached deposit; revoking refunds the same amount like the standard implementation.
+#[near_bindgen]
+impl NonFungibleTokenApproval for Contract {
+    #[payable]
+    fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>) -> Option<Promise> {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(env::predecessor_account_id(), owner_id, "Predecessor must be token owner.");
+        assert!(!self.soulbound.contains(&token_id), "Soulbound tokens cannot be approved");
+
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let next_approval_id_by_id = self.tokens.next_approval_id_by_id.as_mut().unwrap();
+        let mut approved_account_ids = approvals_by_id.get(&token_id).unwrap_or_default();
+        let approval_id = next_approval_id_by_id.get(&token_id).unwrap_or(1);
+        let old_approval_id = approved_account_ids.insert(account_id.clone(), approval_id);
+        approvals_by_id.insert(&token_id, &approved_account_ids);
+        next_approval_id_by_id.insert(&token_id, &(approval_id + 1));
+
+        //replacing an approval of the same account uses no storage
+        let storage_used = if old_approval_id.is_none() { bytes_for_approved_account_id(&account_id) } else { 0 };
+        self.charge(0, env::storage_byte_cost() * Balance::from(storage_used), "approval");
+
+        msg.map(|msg| {
+            ext_nft_approval_receiver::ext(account_id)
+                .with_static_gas(env::prepaid_gas() - GAS_FOR_NFT_APPROVE)
+                .nft_on_approve(token_id, owner_id, approval_id, msg)
+        })
+    }
+
+    #[payable]
+    fn nft_revoke(&mut self, token_id: TokenId, account_id: AccountId) {
+        self.tokens.nft_revoke(token_id, account_id)
+    }
+
+    #[payable]
+    fn nft_revoke_all(&mut self, token_id: TokenId) {
+        self.tokens.nft_revoke_all(token_id)
+    }
+
+    fn nft_is_approved(&self, token_id: TokenId, approved_account_id: AccountId, approval_id: Option<u64>) -> bool {
+        self.tokens.nft_is_approved(token_id, approved_account_id, approval_id)
+    }
+}
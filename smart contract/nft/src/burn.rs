@@ -0,0 +1,52 @@
+use near_contract_standards::non_fungible_token::events::NftBurn;
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::{assert_one_yocto, env, near_bindgen, Balance, Promise};
+
+use crate::{Contract, ContractExt};
+
+#[near_bindgen]
+impl Contract {
+    /// Destroys the reading `token_id` with its metadata, approvals, royalties, question and annotations.
+    /// Soulbound readings can be burned too.
+    /// Only the token owner can call this, with exactly 1 yoctoNEAR attached. The released storage
+    /// is credited to the owner's storage balance, or sent back if they are not registered.
+    /// `get_num` keeps counting burned readings, so token ids are never reused.
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(env::predecessor_account_id(), owner_id, "Only the token owner can burn it");
+        let initial_storage_usage = env::storage_usage();
+
+        self.tokens.owner_by_id.remove(&token_id);
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            token_metadata_by_id.remove(&token_id);
+        }
+        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
+            let mut owner_tokens = tokens_per_owner.get(&owner_id).expect("Unable to access tokens per owner");
+            owner_tokens.remove(&token_id);
+            if owner_tokens.is_empty() {
+                tokens_per_owner.remove(&owner_id);
+            } else {
+                tokens_per_owner.insert(&owner_id, &owner_tokens);
+            }
+        }
+        if let Some(approvals_by_id) = &mut self.tokens.approvals_by_id {
+            approvals_by_id.remove(&token_id);
+        }
+        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
+            next_approval_id_by_id.remove(&token_id);
+        }
+        self.royalties.remove(&token_id);
+        self.questions.remove(&token_id);
+        self.soulbound.remove(&token_id);
+        if let Some(mut annotations) = self.annotations.remove(&token_id) {
+            annotations.clear();
+        }
+
+        let released = env::storage_byte_cost() * Balance::from(initial_storage_usage - env::storage_usage());
+        let refund = self.internal_storage_release(&owner_id, released) + 1;
+        NftBurn { owner_id: &owner_id, token_ids: &[&token_id], authorized_id: None, memo: None }.emit();
+        Promise::new(owner_id).transfer(refund);
+    }
+}
@@ -0,0 +1,125 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::deck::Deck;
+use crate::events::ConfigUpdated;
+use crate::payout::assert_total_royalty;
+use crate::roles::Role;
+use crate::spread::Spread;
+use crate::{
+    Contract, ContractExt, DRAW_PRICE, MAX_QUESTION_LEN, MAX_READING_LEN, MAX_TITLE_LEN, MINT_PRICE, VAULT, VAULT_ROYALTY,
+};
+
+/// Prices and accounts the owner can change without redeploying.
+/// `vault_royalty` is in basis points of secondary sales, paid to `vault_id` for readings minted from now on.
+/// The `max_*_len` limits are in bytes of UTF-8: `max_reading_len` for the reading, `max_question_len`
+/// for a sealed question's ciphertext and its revealed text, `max_title_len` for the generated title.
+/// Spread and card names are checked against `max_title_len` when registered, so a paid draw can always be minted.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    pub draw_price: U128,
+    pub mint_price: U128,
+    pub vault_id: AccountId,
+    pub vault_royalty: u32,
+    pub max_reading_len: u32,
+    pub max_question_len: u32,
+    pub max_title_len: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            draw_price: DRAW_PRICE.into(),
+            mint_price: MINT_PRICE.into(),
+            vault_id: AccountId::new_unchecked(VAULT.to_string()),
+            vault_royalty: VAULT_ROYALTY,
+            max_reading_len: MAX_READING_LEN,
+            max_question_len: MAX_QUESTION_LEN,
+            max_title_len: MAX_TITLE_LEN,
+        }
+    }
+}
+
+impl Config {
+    pub fn assert_valid(&self) {
+        assert!(self.draw_price.0 > 0, "Draw price must be greater than 0");
+        assert!(self.mint_price.0 > 0, "Mint price must be greater than 0");
+        assert!(
+            self.vault_id != env::current_account_id(),
+            "Vault must be a different account than the contract"
+        );
+        assert_total_royalty(self.vault_royalty);
+        assert!(self.max_reading_len > 0, "Max reading length must be greater than 0");
+        assert!(self.max_question_len > 0, "Max question length must be greater than 0");
+        assert!(self.max_title_len > 0, "Max title length must be greater than 0");
+    }
+
+    pub fn assert_reading_len(&self, reading: &str) {
+        assert_max_len("Reading", reading.len(), self.max_reading_len);
+    }
+
+    pub fn assert_question_len(&self, question: &[u8]) {
+        assert_max_len("Question", question.len(), self.max_question_len);
+    }
+
+    pub fn assert_title_len(&self, title: &str) {
+        assert_max_len("Title", title.len(), self.max_title_len);
+    }
+
+    //checks every reading of `spread` drawn from `deck` can be minted, before anything is paid for
+    pub fn assert_title_fits(&self, spread: &Spread, deck: &Deck) {
+        let len = spread.max_title_len(deck);
+        assert!(
+            len <= self.max_title_len as usize,
+            "Titles of readings of spread {} from deck {} can be {} bytes, more than the max title length of {}",
+            spread.name,
+            deck.name,
+            len,
+            self.max_title_len
+        );
+    }
+}
+
+fn assert_max_len(what: &str, len: usize, max_len: u32) {
+    assert!(len <= max_len as usize, "{} must be at most {} bytes, got {}", what, max_len, len);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Replaces the contract config. Only the owner or an admin can call this,
+    /// and only the owner can change `vault_id` and `vault_royalty`, which decide where revenue goes.
+    pub fn set_config(&mut self, config: Config) {
+        self.assert_role(Role::Admin);
+        if env::predecessor_account_id() != self.tokens.owner_id {
+            assert!(
+                config.vault_id == self.config.vault_id && config.vault_royalty == self.config.vault_royalty,
+                "Only the contract owner can change the vault"
+            );
+        }
+        config.assert_valid();
+        if config.max_title_len < self.config.max_title_len {
+            for spread in self.spreads.values() {
+                for deck in self.decks.values() {
+                    config.assert_title_fits(&spread, &deck);
+                }
+            }
+        }
+        let artist_royalty = self.decks.values().map(|deck| deck.artist_royalty).max().unwrap_or(0);
+        assert_total_royalty(config.vault_royalty + artist_royalty);
+        ConfigUpdated {
+            updated_by: &env::predecessor_account_id(),
+            old_config: &self.config,
+            new_config: &config,
+        }
+        .emit();
+        self.config = config;
+    }
+
+    //view current config
+    pub fn get_config(&self) -> Config {
+        self.config.clone()
+    }
+}
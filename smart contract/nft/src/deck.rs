@@ -0,0 +1,353 @@
+/*!
+The tarot deck and the registry of art decks.
+NOTES:
+  - Cards are addressed by index: 0-21 are the Major Arcana in order, 22-77 are the Minor Arcana,
+    suit by suit (Wands, Cups, Swords, Pentacles), each running Ace to Ten then Page, Knight,
+    Queen and King.
+  - Arcana, suit, rank and element follow from the index. Names and artwork come from the `Deck`
+    the card is drawn from, so the owner can register new art decks without a code change.
+  - A deck holds either the 22 Major Arcana or all 78 cards. A registered deck can grow from 22
+    to 78 cards but never shrink, so pending draws always point at existing cards.
+  - The default `celestial` deck starts with the 22 Major Arcana, and gets the Minor Arcana by
+    registering it again with all 78 cards once their artwork is pinned.
+*/
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{near_bindgen, AccountId};
+use near_sdk::serde::{Deserialize, Serialize};
+
+use crate::payout::assert_total_royalty;
+use crate::roles::Role;
+use crate::{Contract, ContractExt};
+
+pub const MAJOR_ARCANA_COUNT: usize = 22;
+pub const DECK_SIZE: usize = MAJOR_ARCANA_COUNT + 4 * RANKS.len();
+pub const DEFAULT_DECK: &str = "celestial";
+pub const MAX_DECK_ID_LEN: usize = 32;
+
+pub const MAJOR_ARCANA_CARD_URI: &str = "ipfs://bafybeifrqo4oorpn2y2l7vy5y4v4tqebvho5q5hg5rfsx2rafzng3u556q/";
+pub const MAJOR_ARCANA_NAME: [&str; MAJOR_ARCANA_COUNT] = [
+    "0 The Fool",
+    "I The Magician",
+    "II The High Priestess",
+    "III The Empress",
+    "IV The Emperor",
+    "V The Hierophant",
+    "VI The Lovers",
+    "VII The Chariot",
+    "VIII Strength",
+    "IX The Hermit",
+    "X The Wheel of Fortune",
+    "XI Justice",
+    "XII The Hanged Man",
+    "XIII Death",
+    "XIV Temperance",
+    "XV The Devil",
+    "XVI The Tower",
+    "XVII The Star",
+    "XVIII The Moon",
+    "XIX The Sun",
+    "XX Judgement",
+    "XXI The World"
+];
+const MAJOR_ARCANA_ELEMENT: [Element; MAJOR_ARCANA_COUNT] = [
+    Element::Air,   // The Fool, Uranus
+    Element::Air,   // The Magician, Mercury
+    Element::Water, // The High Priestess, Moon
+    Element::Earth, // The Empress, Venus
+    Element::Fire,  // The Emperor, Aries
+    Element::Earth, // The Hierophant, Taurus
+    Element::Air,   // The Lovers, Gemini
+    Element::Water, // The Chariot, Cancer
+    Element::Fire,  // Strength, Leo
+    Element::Earth, // The Hermit, Virgo
+    Element::Fire,  // The Wheel of Fortune, Jupiter
+    Element::Air,   // Justice, Libra
+    Element::Water, // The Hanged Man, Neptune
+    Element::Water, // Death, Scorpio
+    Element::Fire,  // Temperance, Sagittarius
+    Element::Earth, // The Devil, Capricorn
+    Element::Fire,  // The Tower, Mars
+    Element::Air,   // The Star, Aquarius
+    Element::Water, // The Moon, Pisces
+    Element::Fire,  // The Sun, Sun
+    Element::Fire,  // Judgement, Pluto
+    Element::Earth, // The World, Saturn
+];
+const RANKS: [&str; 14] = [
+    "Ace", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten",
+    "Page", "Knight", "Queen", "King",
+];
+const SUITS: [Suit; 4] = [Suit::Wands, Suit::Cups, Suit::Swords, Suit::Pentacles];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Arcana {
+    Major,
+    Minor,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Suit {
+    Wands,
+    Cups,
+    Swords,
+    Pentacles,
+}
+
+impl Suit {
+    pub fn name(self) -> &'static str {
+        match self {
+            Suit::Wands => "Wands",
+            Suit::Cups => "Cups",
+            Suit::Swords => "Swords",
+            Suit::Pentacles => "Pentacles",
+        }
+    }
+
+    pub fn element(self) -> Element {
+        match self {
+            Suit::Wands => Element::Fire,
+            Suit::Cups => Element::Water,
+            Suit::Swords => Element::Air,
+            Suit::Pentacles => Element::Earth,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Element {
+    Fire,
+    Water,
+    Air,
+    Earth,
+}
+
+/// A card of the deck with its structured attributes.
+/// `rank` is the Major Arcana number (0-21), or 1-14 within a suit (Ace = 1, King = 14).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Card {
+    pub index: u8,
+    pub name: String,
+    pub arcana: Arcana,
+    pub suit: Option<Suit>,
+    pub rank: u8,
+    pub element: Element,
+    pub media: String,
+    pub media_hash: Option<Base64VecU8>,
+}
+
+/// Artwork of one card in a `Deck`. `media` is relative to the deck's `base_uri`
+/// and `media_hash` is the sha256 of the file, if known.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DeckCard {
+    pub name: String,
+    pub media: String,
+    pub media_hash: Option<Base64VecU8>,
+}
+
+/// An art deck. `cards[i]` is card `i` in the standard order.
+/// Readings drawn from it pay `artist_royalty` basis points of secondary sales to `artist_id`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Deck {
+    pub name: String,
+    pub base_uri: String,
+    pub cards: Vec<DeckCard>,
+    #[serde(default)]
+    pub artist_id: Option<AccountId>,
+    #[serde(default)]
+    pub artist_royalty: u32,
+}
+
+impl Deck {
+    /// The deck every deployment starts with, the 22 Major Arcana. It can be replaced with all
+    /// 78 cards with `set_deck` once artwork for the Minor Arcana is pinned.
+    pub fn celestial() -> Self {
+        Self {
+            name: "Celestial".to_string(),
+            base_uri: MAJOR_ARCANA_CARD_URI.to_string(),
+            cards: (0..MAJOR_ARCANA_COUNT)
+                .map(|i| DeckCard {
+                    name: standard_name(i),
+                    media: format!("{}.png", i),
+                    media_hash: None,
+                })
+                .collect(),
+            artist_id: None,
+            artist_royalty: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn card(&self, index: u8) -> Card {
+        let i = index as usize;
+        assert!(i < self.size(), "Card {} not found", index);
+        let card = &self.cards[i];
+        let (arcana, suit, rank, element) = if i < MAJOR_ARCANA_COUNT {
+            (Arcana::Major, None, index, MAJOR_ARCANA_ELEMENT[i])
+        } else {
+            let minor = i - MAJOR_ARCANA_COUNT;
+            let suit = SUITS[minor / RANKS.len()];
+            (Arcana::Minor, Some(suit), (minor % RANKS.len()) as u8 + 1, suit.element())
+        };
+        Card {
+            index,
+            name: card.name.clone(),
+            arcana,
+            suit,
+            rank,
+            element,
+            media: format!("{}{}", self.base_uri, card.media),
+            media_hash: card.media_hash.clone(),
+        }
+    }
+
+    pub fn assert_valid(&self) {
+        assert!(!self.name.is_empty(), "Deck name must not be empty");
+        assert!(!self.base_uri.is_empty(), "Deck base URI must not be empty");
+        assert!(
+            self.size() == MAJOR_ARCANA_COUNT || self.size() == DECK_SIZE,
+            "Deck must have {} or {} cards",
+            MAJOR_ARCANA_COUNT,
+            DECK_SIZE
+        );
+        for card in &self.cards {
+            assert!(!card.name.is_empty() && !card.media.is_empty(), "Card name and media must not be empty");
+            assert!(
+                !matches!(&card.media_hash, Some(hash) if hash.0.len() != 32),
+                "Card media hash must be a 32-byte sha256 hash"
+            );
+        }
+        assert!(self.artist_id.is_some() || self.artist_royalty == 0, "Artist royalty requires an artist");
+    }
+}
+
+//name of card `i` in the standard order
+fn standard_name(i: usize) -> String {
+    if i < MAJOR_ARCANA_COUNT {
+        return MAJOR_ARCANA_NAME[i].to_string();
+    }
+    let minor = i - MAJOR_ARCANA_COUNT;
+    format!("{} of {}", RANKS[minor % RANKS.len()], SUITS[minor / RANKS.len()].name())
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Registers or replaces the deck `deck_id`. Only the owner or an admin can call this.
+    /// A registered deck of the 22 Major Arcana can grow to all 78 cards, but no deck can lose
+    /// cards, since pending draws refer to them by index.
+    pub fn set_deck(&mut self, deck_id: String, deck: Deck) {
+        self.assert_role(Role::Admin);
+        assert!(
+            !deck_id.is_empty() && deck_id.len() <= MAX_DECK_ID_LEN,
+            "Deck id must be between 1 and {} bytes",
+            MAX_DECK_ID_LEN
+        );
+        deck.assert_valid();
+        assert_total_royalty(self.config.vault_royalty + deck.artist_royalty);
+        if let Some(old) = self.decks.get(&deck_id) {
+            assert!(deck.size() >= old.size(), "A registered deck cannot lose cards");
+        }
+        for spread in self.spreads.values() {
+            self.config.assert_title_fits(&spread, &deck);
+        }
+        self.decks.insert(&deck_id, &deck);
+    }
+
+    //view a deck
+    pub fn get_deck(&self, deck_id: String) -> Option<Deck> {
+        self.decks.get(&deck_id)
+    }
+
+    //view the registered decks with their name and number of cards
+    pub fn get_decks(&self) -> Vec<(String, String, u8)> {
+        self.decks.iter().map(|(id, deck)| (id, deck.name.clone(), deck.size() as u8)).collect()
+    }
+
+    //view a card of `deck_id`, the default deck if omitted
+    pub fn get_card(&self, card_index: u8, deck_id: Option<String>) -> Card {
+        self.internal_deck(&deck_id.unwrap_or_else(|| DEFAULT_DECK.to_string())).card(card_index)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    fn full_deck() -> Deck {
+        Deck {
+            name: "Full".to_string(),
+            base_uri: "ipfs://full/".to_string(),
+            cards: (0..DECK_SIZE)
+                .map(|i| DeckCard { name: standard_name(i), media: format!("{}.png", i), media_hash: None })
+                .collect(),
+            artist_id: None,
+            artist_royalty: 0,
+        }
+    }
+
+    #[test]
+    fn test_deck() {
+        let full = full_deck();
+        full.assert_valid();
+        let deck: Vec<Card> = (0..DECK_SIZE as u8).map(|i| full.card(i)).collect();
+        assert_eq!(deck.len(), 78);
+        let mut names: Vec<&str> = deck.iter().map(|card| card.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), 78);
+        assert_eq!(deck.iter().filter(|card| card.arcana == Arcana::Major).count(), 22);
+        assert_eq!(deck.iter().filter(|card| card.suit == Some(Suit::Cups)).count(), 14);
+    }
+
+    #[test]
+    fn test_minor_arcana() {
+        let deck = full_deck();
+        let ace = deck.card(22);
+        assert_eq!(ace.name, "Ace of Wands");
+        assert_eq!((ace.rank, ace.element), (1, Element::Fire));
+
+        let page = deck.card(60);
+        assert_eq!(page.name, "Page of Swords");
+        assert_eq!((page.suit, page.rank, page.element), (Some(Suit::Swords), 11, Element::Air));
+
+        let king = deck.card(77);
+        assert_eq!(king.name, "King of Pentacles");
+        assert_eq!(king.media, "ipfs://full/77.png");
+    }
+
+    #[test]
+    #[should_panic(expected = "Card 78 not found")]
+    fn test_out_of_deck() {
+        full_deck().card(78);
+    }
+
+    #[test]
+    #[should_panic(expected = "Card 22 not found")]
+    fn test_major_arcana_deck() {
+        let deck = Deck::celestial();
+        deck.assert_valid();
+        assert_eq!(deck.card(21).name, "XXI The World");
+        deck.card(22);
+    }
+
+    #[test]
+    #[should_panic(expected = "Card media hash must be a 32-byte sha256 hash")]
+    fn test_invalid_media_hash() {
+        let mut deck = Deck::celestial();
+        deck.cards[0].media_hash = Some(vec![0; 16].into());
+        deck.assert_valid();
+    }
+}
@@ -0,0 +1,232 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, Balance, BlockHeight};
+use std::str::FromStr;
+
+use crate::deck::{Arcana, Card, Deck, Suit};
+
+/// Number of blocks after its commit during which a draw is expected to be revealed.
+/// Past that, the drawer can take the price back with `refund_draw`.
+pub const DRAW_REVEAL_TIMEOUT: BlockHeight = 600;
+
+/// Maximum length of a `ReadingReference` URL in bytes.
+pub const MAX_REFERENCE_LEN: usize = 256;
+
+/// Borsh size of a `DrawnCard`.
+const DRAWN_CARD_BYTES: u64 = 2;
+
+/// Spread paid for with `draw_cards`, or in a fungible token with `ft_on_transfer`, which has not been minted yet.
+/// Its `cards` stay empty until `reveal_draw` draws them in a later block.
+/// `price` is in yoctoNEAR, or in `ft_token_id` if the draw was paid in a fungible token.
+/// It is removed from state once `nft_mint` consumes it or `refund_draw` refunds it.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Draw {
+    pub drawer: AccountId,
+    pub deck_id: String,
+    pub spread_id: String,
+    pub spread_name: String,
+    pub positions: Vec<String>,
+    pub cards: Vec<DrawnCard>,
+    pub price: Balance,
+    pub ft_token_id: Option<AccountId>,
+    pub block_height: BlockHeight,
+    pub revealed_at: Option<BlockHeight>,
+}
+
+/// A card placed at the spread position of the same index.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DrawnCard {
+    pub card_index: u8,
+    pub orientation: Orientation,
+}
+
+/// Which way up a drawn card lies. In JSON it is "upright" or "reversed", and "reverse",
+/// the name used by readings minted before, is read as `Reversed`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    #[serde(alias = "reverse")]
+    Reversed,
+    Upright,
+}
+
+impl Orientation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Orientation::Upright => "upright",
+            Orientation::Reversed => "reversed",
+        }
+    }
+}
+
+impl FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "upright" => Ok(Orientation::Upright),
+            "reversed" | "reverse" => Ok(Orientation::Reversed),
+            _ => Err(format!("Invalid orientation: {}", s)),
+        }
+    }
+}
+
+impl DrawnCard {
+    pub fn card(&self, deck: &Deck) -> Card {
+        deck.card(self.card_index)
+    }
+
+    pub fn view(&self, label: &str, deck: &Deck) -> DrawnCardView {
+        let card = self.card(deck);
+        DrawnCardView {
+            label: label.to_string(),
+            card_index: self.card_index,
+            card: card.name,
+            card_uri: card.media,
+            orientation: self.orientation,
+        }
+    }
+
+    fn layout<'a>(&self, spread_position: &'a str, deck: &Deck) -> ReadingCard<'a> {
+        let card = self.card(deck);
+        ReadingCard {
+            spread_position,
+            card_index: self.card_index,
+            card: card.name,
+            card_uri: card.media,
+            arcana: card.arcana,
+            suit: card.suit,
+            orientation: self.orientation,
+        }
+    }
+}
+
+impl Draw {
+    pub fn is_revealed(&self) -> bool {
+        self.revealed_at.is_some()
+    }
+
+    /// Bytes `reveal_draw` adds to the stored draw, paid for by the drawer at commit.
+    pub fn reveal_storage_bytes(&self) -> u64 {
+        self.positions.len() as u64 * DRAWN_CARD_BYTES + std::mem::size_of::<BlockHeight>() as u64
+    }
+
+    /// `deck` is the registered deck `deck_id`.
+    pub fn view(&self, draw_id: u64, deck: &Deck) -> DrawView {
+        DrawView {
+            draw_id: draw_id.into(),
+            drawer: self.drawer.clone(),
+            deck_id: self.deck_id.clone(),
+            spread_id: self.spread_id.clone(),
+            cards: self.card_views(deck),
+            block_height: self.block_height.into(),
+            revealed_at: self.revealed_at.map(U64),
+        }
+    }
+
+    fn card_views(&self, deck: &Deck) -> Vec<DrawnCardView> {
+        self.positions.iter().zip(&self.cards).map(|(label, card)| card.view(label, deck)).collect()
+    }
+
+    /// JSON describing the reading, kept in the minted token's `extra`, see `ReadingLayout`.
+    pub fn layout_json(
+        &self,
+        deck: &Deck,
+        question_hash: Option<&Base64VecU8>,
+        soulbound: bool,
+        verified_by: Option<&AccountId>,
+    ) -> String {
+        near_sdk::serde_json::to_string(&ReadingLayout {
+            deck: &self.deck_id,
+            spread: &self.spread_id,
+            block_height: self.block_height.into(),
+            revealed_at: self.revealed_at.map(U64),
+            cards: self
+                .positions
+                .iter()
+                .zip(&self.cards)
+                .map(|(spread_position, card)| card.layout(spread_position, deck))
+                .collect(),
+            question_hash,
+            soulbound,
+            verified_by,
+        })
+        .unwrap()
+    }
+}
+
+/// The `extra` of a reading: the deck and spread drawn from, the block the draw was committed in
+/// and the one its cards were drawn in, each card with its attributes, the hash of the sealed
+/// question, whether the reading is soulbound and the interpreter that signed it.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct ReadingLayout<'a> {
+    deck: &'a str,
+    spread: &'a str,
+    block_height: U64,
+    revealed_at: Option<U64>,
+    cards: Vec<ReadingCard<'a>>,
+    question_hash: Option<&'a Base64VecU8>,
+    soulbound: bool,
+    verified_by: Option<&'a AccountId>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct ReadingCard<'a> {
+    spread_position: &'a str,
+    card_index: u8,
+    card: String,
+    card_uri: String,
+    arcana: Arcana,
+    suit: Option<Suit>,
+    orientation: Orientation,
+}
+
+/// Off-chain JSON describing a reading, set as the token's NEP-177 `reference`.
+/// `reference_hash` is the sha256 of that JSON. The contract cannot fetch the file,
+/// it only checks the hash is well formed so clients can verify what they download.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReadingReference {
+    pub reference: String,
+    pub reference_hash: Base64VecU8,
+}
+
+impl ReadingReference {
+    pub fn assert_valid(&self) {
+        assert!(!self.reference.is_empty(), "Reference must not be empty");
+        assert!(
+            self.reference.len() <= MAX_REFERENCE_LEN,
+            "Reference must be at most {} bytes",
+            MAX_REFERENCE_LEN
+        );
+        assert_eq!(self.reference_hash.0.len(), 32, "Reference hash must be a 32-byte sha256 hash");
+    }
+}
+
+/// JSON view of a pending draw, returned by `draw_cards`, `reveal_draw` and `get_draw`.
+/// `cards` is empty until the draw is revealed.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DrawView {
+    pub draw_id: U64,
+    pub drawer: AccountId,
+    pub deck_id: String,
+    pub spread_id: String,
+    pub cards: Vec<DrawnCardView>,
+    pub block_height: U64,
+    pub revealed_at: Option<U64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DrawnCardView {
+    pub label: String,
+    pub card_index: u8,
+    pub card: String,
+    pub card_uri: String,
+    pub orientation: Orientation,
+}
@@ -0,0 +1,184 @@
+/*!
+Contract events, logged in the NEP-297 format under the `celestial` standard so an indexer can
+follow activity that the NEP-171 events do not cover.
+NOTES:
+  - Each event has its own schema version, logged in `version`, 1.0.0 as first released. Bump it
+    whenever the fields of a released event change, so indexers can tell old and new logs apart.
+  - Events are only logged once the action can no longer fail, e.g. `funds_withdrawn` is logged
+    when the transfer to the vault succeeded.
+  - Prices and amounts are in yoctoNEAR, unless `ft_token_id` names the fungible token they are in.
+*/
+
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, AccountId};
+
+use crate::config::Config;
+use crate::draw::DrawnCardView;
+use crate::pause::PauseState;
+
+pub const CARD_DRAWN_VERSION: &str = "1.0.0";
+pub const DRAW_REFUNDED_VERSION: &str = "1.0.0";
+pub const READING_MINTED_VERSION: &str = "1.0.0";
+pub const CONFIG_UPDATED_VERSION: &str = "1.0.0";
+pub const FUNDS_WITHDRAWN_VERSION: &str = "1.0.0";
+pub const PAUSE_UPDATED_VERSION: &str = "1.0.0";
+pub const READING_ANNOTATED_VERSION: &str = "1.0.0";
+
+/// Data to log when the cards of a draw are revealed. To log this event, call `.emit()`.
+#[must_use]
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CardDrawn<'a> {
+    pub drawer: &'a AccountId,
+    pub draw_id: U64,
+    pub deck_id: &'a str,
+    pub spread_id: &'a str,
+    pub cards: &'a [DrawnCardView],
+    pub price: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ft_token_id: Option<&'a AccountId>,
+}
+
+impl CardDrawn<'_> {
+    pub fn emit(self) {
+        CelestialEvent::new(CARD_DRAWN_VERSION, CelestialEventKind::CardDrawn(&[self])).emit()
+    }
+}
+
+/// Data to log when a draw that was never revealed is refunded. To log this event, call `.emit()`.
+#[must_use]
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DrawRefunded<'a> {
+    pub drawer: &'a AccountId,
+    pub draw_id: U64,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ft_token_id: Option<&'a AccountId>,
+}
+
+impl DrawRefunded<'_> {
+    pub fn emit(self) {
+        CelestialEvent::new(DRAW_REFUNDED_VERSION, CelestialEventKind::DrawRefunded(&[self])).emit()
+    }
+}
+
+/// Data to log when a draw is minted as a reading. To log this event, call `.emit()`.
+#[must_use]
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReadingMinted<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_id: &'a TokenId,
+    pub draw_id: U64,
+    pub price: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ft_token_id: Option<&'a AccountId>,
+}
+
+impl ReadingMinted<'_> {
+    pub fn emit(self) {
+        CelestialEvent::new(READING_MINTED_VERSION, CelestialEventKind::ReadingMinted(&[self])).emit()
+    }
+}
+
+/// Data to log when the owner changes the config. To log this event, call `.emit()`.
+#[must_use]
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigUpdated<'a> {
+    pub updated_by: &'a AccountId,
+    pub old_config: &'a Config,
+    pub new_config: &'a Config,
+}
+
+impl ConfigUpdated<'_> {
+    pub fn emit(self) {
+        CelestialEvent::new(CONFIG_UPDATED_VERSION, CelestialEventKind::ConfigUpdated(&[self])).emit()
+    }
+}
+
+/// Data to log when revenue reached the vault. To log this event, call `.emit()`.
+#[must_use]
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundsWithdrawn<'a> {
+    pub vault_id: &'a AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ft_token_id: Option<&'a AccountId>,
+}
+
+impl FundsWithdrawn<'_> {
+    pub fn emit(self) {
+        CelestialEvent::new(FUNDS_WITHDRAWN_VERSION, CelestialEventKind::FundsWithdrawn(&[self])).emit()
+    }
+}
+
+/// Data to log when a pauser changes the pause flags. To log this event, call `.emit()`.
+#[must_use]
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseUpdated<'a> {
+    pub updated_by: &'a AccountId,
+    pub paused: &'a PauseState,
+}
+
+impl PauseUpdated<'_> {
+    pub fn emit(self) {
+        CelestialEvent::new(PAUSE_UPDATED_VERSION, CelestialEventKind::PauseUpdated(&[self])).emit()
+    }
+}
+
+/// Data to log when the owner of a reading annotates it. `index` is the position of the new
+/// annotation, see `get_annotations`. To log this event, call `.emit()`.
+#[must_use]
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReadingAnnotated<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_id: &'a TokenId,
+    pub index: U64,
+}
+
+impl ReadingAnnotated<'_> {
+    pub fn emit(self) {
+        CelestialEvent::new(READING_ANNOTATED_VERSION, CelestialEventKind::ReadingAnnotated(&[self])).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct CelestialEvent<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: CelestialEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+enum CelestialEventKind<'a> {
+    CardDrawn(&'a [CardDrawn<'a>]),
+    DrawRefunded(&'a [DrawRefunded<'a>]),
+    ReadingMinted(&'a [ReadingMinted<'a>]),
+    ConfigUpdated(&'a [ConfigUpdated<'a>]),
+    FundsWithdrawn(&'a [FundsWithdrawn<'a>]),
+    PauseUpdated(&'a [PauseUpdated<'a>]),
+    ReadingAnnotated(&'a [ReadingAnnotated<'a>]),
+}
+
+impl<'a> CelestialEvent<'a> {
+    fn new(version: &'static str, event_kind: CelestialEventKind<'a>) -> Self {
+        Self { standard: "celestial", version, event_kind }
+    }
+
+    fn emit(self) {
+        let json = near_sdk::serde_json::to_string(&self).unwrap_or_else(|_| env::abort());
+        env::log_str(&format!("EVENT_JSON:{}", json));
+    }
+}
@@ -16,52 +16,81 @@ NOTES:
     keys on its account.
 */
 
-
+use near_contract_standards::fungible_token::core::ext_ft_core;
 use near_contract_standards::non_fungible_token::metadata::{
     NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
 };
+use near_contract_standards::non_fungible_token::events::NftMint;
 use near_contract_standards::non_fungible_token::{Token, TokenId};
 use near_contract_standards::non_fungible_token::NonFungibleToken;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::LazyOption;
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, UnorderedMap, Vector};
+use near_sdk::json_types::U64;
+use std::collections::HashMap;
+
 use near_sdk::{
-    env, near_bindgen, AccountId, BorshStorageKey, PanicOnDefault, Promise, PromiseOrValue,
+    assert_one_yocto, env, near_bindgen, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault, Promise,
+    PublicKey, StorageUsage,
 };
 
+mod annotation;
+mod approval;
+mod burn;
+mod config;
+pub mod deck;
+mod draw;
+mod events;
+mod migrate;
+// `nft_mint` takes the optional parts of a reading as separate JSON arguments, and so does the
+// `ContractExt` method `near_bindgen` generates for it, which only the module can allow
+#[allow(clippy::too_many_arguments)]
+mod mint;
+mod nft_core;
+mod oracle;
+mod pause;
+mod payment;
+mod payout;
+mod question;
+pub mod random;
+mod roles;
+mod spread;
+mod storage;
+mod treasury;
+
+pub use crate::annotation::Annotation;
+pub use crate::config::Config;
+pub use crate::deck::{Card, Deck, DeckCard};
+use crate::deck::DEFAULT_DECK;
+use crate::events::{CardDrawn, DrawRefunded};
+pub use crate::draw::{Draw, DrawView, DrawnCard, DrawnCardView, Orientation, ReadingReference, DRAW_REVEAL_TIMEOUT};
+pub use crate::migrate::StateVersion;
+pub use crate::oracle::ReadingSignature;
+pub use crate::pause::PauseState;
+pub use crate::payment::FtPrice;
+pub use crate::payout::Payout;
+pub use crate::question::{Question, SealedQuestion};
+pub use crate::roles::Role;
+pub use crate::spread::Spread;
+use crate::spread::{default_spreads, SINGLE_SPREAD};
+pub use crate::treasury::{Treasury, TreasuryView};
+use crate::random::{rand_bool, rand_distinct, RandomSource, SeedStream};
+
 ///////////////////////////////////////////////////////
 // CONST                                             //
 //////////////////////////////////////////////////////
 
 const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 288 288'%3E%3Cg id='l' data-name='l'%3E%3Cpath d='M187.58,79.81l-30.1,44.69a3.2,3.2,0,0,0,4.75,4.2L191.86,103a1.2,1.2,0,0,1,2,.91v80.46a1.2,1.2,0,0,1-2.12.77L102.18,77.93A15.35,15.35,0,0,0,90.47,72.5H87.34A15.34,15.34,0,0,0,72,87.84V201.16A15.34,15.34,0,0,0,87.34,216.5h0a15.35,15.35,0,0,0,13.08-7.31l30.1-44.69a3.2,3.2,0,0,0-4.75-4.2L96.14,186a1.2,1.2,0,0,1-2-.91V104.61a1.2,1.2,0,0,1,2.12-.77l89.55,107.23a15.35,15.35,0,0,0,11.71,5.43h3.13A15.34,15.34,0,0,0,216,201.16V87.84A15.34,15.34,0,0,0,200.66,72.5h0A15.35,15.35,0,0,0,187.58,79.81Z'/%3E%3C/g%3E%3C/svg%3E";
+// defaults for `Config`, the owner can change them with `set_config`
 const DRAW_PRICE: u128 =      500_000_000_000_000_000_000_000; //0.5NEAR
 const MINT_PRICE: u128 =    1_000_000_000_000_000_000_000_000; //1NEAR
-const MINT_STORAGE_COST: u128 = 5_870_000_000_000_000_000_000;
 const VAULT: &str = "tarotvault.testnet";
-const MAJOR_ARCANA_CARD_URI: &str = "ipfs://bafybeifrqo4oorpn2y2l7vy5y4v4tqebvho5q5hg5rfsx2rafzng3u556q/";
-const MAJOR_ARCANA_NAME: [&'static str; 22] = [
-    "0 The Fool",
-    "I The Magician",
-    "II The High Priestess",
-    "III The Empress",
-    "IV The Emperor",
-    "V The Hierophant",
-    "VI The Lovers",
-    "VII The Chariot",
-    "VIII Strength",
-    "IX The Hermit",
-    "X The Wheel of Fortune",
-    "XI Justice",
-    "XII The Hanged Man",
-    "XIII Death",
-    "XIV Temperance",
-    "XV The Devil",
-    "XVI The Tower",
-    "XVII The Star",
-    "XVIII The Moon",
-    "XIX The Sun",
-    "XX Judgement",
-    "XXI The World"
-];
+const VAULT_ROYALTY: u32 = 500; //5% of secondary sales
+const MAX_READING_LEN: u32 = 4096;
+const MAX_QUESTION_LEN: u32 = 1024;
+const MAX_TITLE_LEN: u32 = 128;
+const GAS_FOR_REVEAL_DRAW: Gas = Gas(15_000_000_000_000);
+const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_REFUND: Gas = Gas(10_000_000_000_000);
 
 ///////////////////////////////////////////////////////
 // STRUCT                                            //
@@ -72,16 +101,47 @@ const MAJOR_ARCANA_NAME: [&'static str; 22] = [
 pub struct Contract {
     tokens: NonFungibleToken,
     metadata: LazyOption<NFTContractMetadata>,
-    pub minted: i8
+    pub minted: u64,
+    draws: LookupMap<u64, Draw>,
+    next_draw_id: u64,
+    treasury: Treasury,
+    config: Config,
+    spreads: UnorderedMap<String, Spread>,
+    decks: UnorderedMap<String, Deck>,
+    questions: LookupMap<TokenId, Question>,
+    royalties: LookupMap<TokenId, HashMap<AccountId, u32>>,
+    soulbound: LookupSet<TokenId>,
+    storage_deposits: LookupMap<AccountId, Balance>,
+    ft_prices: UnorderedMap<AccountId, FtPrice>,
+    ft_treasuries: LookupMap<AccountId, Treasury>,
+    roles: UnorderedMap<AccountId, Vec<Role>>,
+    pending_owner_id: Option<AccountId>,
+    paused: PauseState,
+    interpreters: UnorderedMap<AccountId, PublicKey>,
+    annotations: LookupMap<TokenId, Vector<Annotation>>,
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
-enum StorageKey {
+pub(crate) enum StorageKey {
     NonFungibleToken,
     Metadata,
     TokenMetadata,
     Enumeration,
     Approval,
+    Draws,
+    Spreads,
+    Decks,
+    Questions,
+    Royalties,
+    StorageDeposits,
+    StateVersion,
+    Roles,
+    FtPrices,
+    FtTreasuries,
+    Soulbound,
+    Interpreters,
+    Annotations,
+    AnnotationsPerToken { token_hash: Vec<u8> },
 }
 
 ///////////////////////////////////////////////////////
@@ -91,7 +151,7 @@ enum StorageKey {
 #[near_bindgen]
 impl Contract {
     /// Initializes the contract owned by `owner_id` with
-    /// default metadata (for example purposes only).
+    /// default metadata and config (for example purposes only).
     #[init]
     pub fn new_default_meta(owner_id: AccountId) -> Self {
         Self::new(
@@ -105,14 +165,18 @@ impl Contract {
                 reference: None,
                 reference_hash: None,
             },
+            None,
         )
     }
 
+    /// Initializes the contract. `config` falls back to `Config::default()` if omitted.
     #[init]
-    pub fn new(owner_id: AccountId, metadata: NFTContractMetadata) -> Self {
+    pub fn new(owner_id: AccountId, metadata: NFTContractMetadata, config: Option<Config>) -> Self {
         //assert!(!env::state_exists(), "Already initialized");
         metadata.assert_valid();
-        Self {
+        let config = config.unwrap_or_default();
+        config.assert_valid();
+        let mut this = Self {
             tokens: NonFungibleToken::new(
                 StorageKey::NonFungibleToken,
                 owner_id,
@@ -121,107 +185,323 @@ impl Contract {
                 Some(StorageKey::Approval),
             ),
             metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
-            minted: 0
+            minted: 0,
+            draws: LookupMap::new(StorageKey::Draws),
+            next_draw_id: 0,
+            treasury: Treasury::default(),
+            config,
+            spreads: UnorderedMap::new(StorageKey::Spreads),
+            decks: UnorderedMap::new(StorageKey::Decks),
+            questions: LookupMap::new(StorageKey::Questions),
+            royalties: LookupMap::new(StorageKey::Royalties),
+            soulbound: LookupSet::new(StorageKey::Soulbound),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            ft_prices: UnorderedMap::new(StorageKey::FtPrices),
+            ft_treasuries: LookupMap::new(StorageKey::FtTreasuries),
+            roles: UnorderedMap::new(StorageKey::Roles),
+            pending_owner_id: None,
+            paused: PauseState::default(),
+            interpreters: UnorderedMap::new(StorageKey::Interpreters),
+            annotations: LookupMap::new(StorageKey::Annotations),
+        };
+        this.decks.insert(&DEFAULT_DECK.to_string(), &Deck::celestial());
+        for (spread_id, spread) in default_spreads() {
+            this.spreads.insert(&spread_id, &spread);
         }
+        migrate::write_state_version();
+        this
     }
 
-    /// Mint a new token with ID=`token_id` belonging to `receiver_id`.
-    ///
-    /// Since this example implements metadata, it also requires per-token metadata to be provided
-    /// in this call. `self.tokens.mint` will also require it to be Some, since
-    /// `StorageKey::TokenMetadata` was provided at initialization.
-    ///
-    /// `self.tokens.mint` will enforce `predecessor_account_id` to equal the `owner_id` given in
-    /// initialization call to `new`.
-    // To-do: 
-    // 1. make payment for mints
-    // 2. draw function
-    
     ///////////////////////////////////////////////////////
     // ENTRY FUNCTIONS                                  //
     //////////////////////////////////////////////////////
     
+    /// Pays for a draw of `spread_id` (a single card if omitted) from `deck_id` (the default deck
+    /// if omitted) and records it as a pending draw of the predecessor. The cards are not drawn yet:
+    /// a `reveal_draw` callback draws them from the random seed of a later block, so whoever
+    /// produces this block cannot pick them. The returned `draw_id` is later passed to `nft_mint`.
     #[payable]
-    pub fn draw_cards() -> Vec<String>{
-        let card_index = Self::rand_num(22) as usize;
-        let card = MAJOR_ARCANA_NAME[card_index].to_string();
-        let position = if Self::rand_num(2) == 0 {"reverse"}else{"upright"}.to_string();
-        let card_uri = format!("{}{}.png", MAJOR_ARCANA_CARD_URI, card_index.to_string());
+    pub fn draw_cards(&mut self, spread_id: Option<String>, deck_id: Option<String>) -> DrawView {
+        self.paused.assert_draws_open();
+        let spread_id = spread_id.unwrap_or_else(|| SINGLE_SPREAD.to_string());
+        let spread = self.spreads.get(&spread_id).expect("Spread not found");
+        let deck_id = deck_id.unwrap_or_else(|| DEFAULT_DECK.to_string());
+        let deck = self.internal_deck(&deck_id);
+        self.config.assert_title_fits(&spread, &deck);
         let deposit = env::attached_deposit();
         assert!(deposit > 0, "Attached deposit must be greater than 0");
         //make sure the deposit is greater than the price
-        assert!(deposit >= DRAW_PRICE, "Attached deposit must be greater than or equal to the draw price: {:?}", DRAW_PRICE);
-        let vault_account_id = AccountId::new_unchecked(VAULT.to_string());
-        Self::pay(DRAW_PRICE, vault_account_id.clone());
-        // vec[card, card_uri, position]
-        vec![card, card_uri, position]
+        let draw_price = spread.price(&self.config);
+        assert!(deposit >= draw_price, "Attached deposit must be greater than or equal to the draw price: {:?}", draw_price);
+        let (draw_id, storage_cost) =
+            self.internal_commit_draw(env::predecessor_account_id(), spread_id, &spread, deck_id, draw_price, None);
+        self.charge(draw_price, storage_cost, "draw");
+        self.draws.get(&draw_id).unwrap().view(draw_id, &deck)
+    }
+
+    /// Draws the cards of the pending draw `draw_id` from the random seed of the current block.
+    /// Only the contract can call this, as the callback scheduled when the draw is paid for.
+    #[private]
+    pub fn reveal_draw(&mut self, draw_id: U64) -> DrawView {
+        let committed = self.draws.get(&draw_id.0).expect("Draw not found");
+        assert!(env::block_height() > committed.block_height, "Draw cannot be revealed in the block it was committed in");
+        let draw = self.internal_reveal_draw(&mut SeedStream::from_env(), draw_id.0);
+        match &committed.ft_token_id {
+            None => self.treasury.record_draw(committed.price),
+            Some(ft_token_id) => self.update_ft_treasury(ft_token_id, |treasury| treasury.record_draw(committed.price)),
+        }
+        CardDrawn {
+            drawer: &draw.drawer,
+            draw_id,
+            deck_id: &draw.deck_id,
+            spread_id: &draw.spread_id,
+            cards: &draw.cards,
+            price: committed.price.into(),
+            ft_token_id: committed.ft_token_id.as_ref(),
+        }
+        .emit();
+        draw
     }
 
+    /// Sends the price of the pending draw `draw_id` back to its drawer, in the currency it was paid in,
+    /// if it was not revealed within `DRAW_REVEAL_TIMEOUT` blocks. Only the drawer can call this, with
+    /// exactly 1 yoctoNEAR attached. The storage the draw used is credited to the drawer's storage
+    /// balance, or sent back in NEAR if they are not registered.
+    /// A draw paid in a fungible token is restored if the token transfer fails, so it can be refunded again.
     #[payable]
-    pub fn nft_mint(
+    pub fn refund_draw(&mut self, draw_id: U64) -> Promise {
+        assert_one_yocto();
+        let draw = self.draws.get(&draw_id.0).expect("Draw not found");
+        assert_eq!(env::predecessor_account_id(), draw.drawer, "Only the drawer can refund this draw");
+        assert!(!draw.is_revealed(), "Draw is already revealed");
+        let refundable_after = draw.block_height + DRAW_REVEAL_TIMEOUT;
+        assert!(env::block_height() > refundable_after, "Draw can only be refunded after block {}", refundable_after);
+        let initial_storage_usage = env::storage_usage();
+        self.draws.remove(&draw_id.0);
+        let released = initial_storage_usage - env::storage_usage() + draw.reveal_storage_bytes();
+        let released = env::storage_byte_cost() * Balance::from(released);
+        match &draw.ft_token_id {
+            None => {
+                let storage_refund = self.internal_storage_release(&draw.drawer, released);
+                DrawRefunded { drawer: &draw.drawer, draw_id, amount: draw.price.into(), ft_token_id: None }.emit();
+                Promise::new(draw.drawer).transfer(draw.price + storage_refund + 1)
+            }
+            //the storage is only released once the tokens are back with the drawer
+            Some(ft_token_id) => ext_ft_core::ext(ft_token_id.clone())
+                .with_attached_deposit(1)
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(draw.drawer.clone(), draw.price.into(), None)
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_RESOLVE_REFUND)
+                        .resolve_refund_draw(draw_id.0, draw, released),
+                ),
+        }
+    }
+
+    /// Completes the refund of the draw `draw_id` paid in a fungible token: releases its storage
+    /// if the transfer to the drawer succeeded, or puts the draw back if it failed. The yoctoNEAR
+    /// attached to `refund_draw` is returned in both cases.
+    #[private]
+    pub fn resolve_refund_draw(
         &mut self,
-        receiver_id: AccountId,
-        card: String,
-        reading: String,
-        _question: String,
-        position: String
-    ) -> Token {
-        let token_id = self.minted;
-        self.minted += 1;
-        let deposit = env::attached_deposit();
-        assert!(deposit > 0, "Attached deposit must be greater than 0");
-        //make sure the deposit is greater than the price
-        assert!(deposit >= MINT_PRICE, "Attached deposit must be greater than or equal to the mint price + storage: {:?}", MINT_PRICE + MINT_STORAGE_COST);
-        let vault_account_id = AccountId::new_unchecked(VAULT.to_string());
-        Self::pay(MINT_PRICE, vault_account_id.clone());
-        self.tokens.internal_mint(token_id.to_string(), receiver_id, Some(Self::set_token_metadata(token_id, card, reading, position)))
+        #[serializer(borsh)] draw_id: u64,
+        #[serializer(borsh)] draw: Draw,
+        #[serializer(borsh)] released: Balance,
+    ) -> bool {
+        let success = env::promise_results_count() == 1
+            && matches!(env::promise_result(0), near_sdk::PromiseResult::Successful(_));
+        let storage_refund = if success {
+            let storage_refund = self.internal_storage_release(&draw.drawer, released);
+            DrawRefunded {
+                drawer: &draw.drawer,
+                draw_id: draw_id.into(),
+                amount: draw.price.into(),
+                ft_token_id: draw.ft_token_id.as_ref(),
+            }
+            .emit();
+            storage_refund
+        } else {
+            self.draws.insert(&draw_id, &draw);
+            0
+        };
+        //the yoctoNEAR attached to `refund_draw` goes back either way, like for a draw paid in NEAR
+        Promise::new(draw.drawer).transfer(storage_refund + 1);
+        success
+    }
+
+    //view a pending draw
+    pub fn get_draw(&self, draw_id: U64) -> Option<DrawView> {
+        self.draws.get(&draw_id.0).map(|draw| draw.view(draw_id.0, &self.internal_deck(&draw.deck_id)))
     }
 
     ///////////////////////////////////////////////////////
     // HELPER FUNCTIONS                                  //
     //////////////////////////////////////////////////////
 
-    pub fn pay(amount: u128, to: AccountId) -> Promise {
-        Promise::new(to).transfer(amount)
+    //charges `price` plus the cost of storage used since `initial_storage_usage`,
+    //storage released since then is credited to the caller's storage balance
+    pub(crate) fn charge_and_refund(&mut self, price: Balance, initial_storage_usage: StorageUsage, action: &str) {
+        let storage_usage = env::storage_usage();
+        if storage_usage < initial_storage_usage {
+            let released = env::storage_byte_cost() * Balance::from(initial_storage_usage - storage_usage);
+            self.internal_storage_credit(&env::predecessor_account_id(), released);
+        }
+        let storage_cost = env::storage_byte_cost() * Balance::from(storage_usage.saturating_sub(initial_storage_usage));
+        self.charge(price, storage_cost, action)
+    }
+
+    //keeps `price` plus `storage_cost`, which is paid from the caller's storage balance first,
+    //and refunds the rest of the deposit
+    pub(crate) fn charge(&mut self, price: Balance, storage_cost: Balance, action: &str) {
+        let storage_cost = self.internal_storage_pay(&env::predecessor_account_id(), storage_cost);
+        let required = price + storage_cost;
+        let deposit = env::attached_deposit();
+        assert!(deposit >= required, "Attached deposit must be greater than or equal to the {} price + storage: {:?}", action, required);
+        let refund = deposit - required;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
+    pub(crate) fn internal_deck(&self, deck_id: &String) -> Deck {
+        self.decks.get(deck_id).expect("Deck not found")
+    }
+
+    //records a pending draw of `spread` from `deck_id` for `drawer`, paid with `price` in NEAR or in `ft_token_id`,
+    //and schedules its reveal. Returns the draw id and the cost of the storage it uses once revealed
+    pub(crate) fn internal_commit_draw(
+        &mut self,
+        drawer: AccountId,
+        spread_id: String,
+        spread: &Spread,
+        deck_id: String,
+        price: Balance,
+        ft_token_id: Option<AccountId>,
+    ) -> (u64, Balance) {
+        let initial_storage_usage = env::storage_usage();
+        let draw = Draw {
+            drawer,
+            deck_id,
+            spread_id,
+            spread_name: spread.name.clone(),
+            positions: spread.positions.clone(),
+            cards: Vec::new(),
+            price,
+            ft_token_id,
+            block_height: env::block_height(),
+            revealed_at: None,
+        };
+        let draw_id = self.next_draw_id;
+        self.next_draw_id += 1;
+        self.draws.insert(&draw_id, &draw);
+        Self::ext(env::current_account_id()).with_static_gas(GAS_FOR_REVEAL_DRAW).reveal_draw(draw_id.into());
+        //the cards written by the reveal are paid for now
+        let storage_used = env::storage_usage() - initial_storage_usage + draw.reveal_storage_bytes();
+        (draw_id, env::storage_byte_cost() * Balance::from(storage_used))
+    }
+
+    //draws the cards of the pending draw `draw_id`, using `rng` for the cards and orientations
+    pub(crate) fn internal_reveal_draw<R: RandomSource>(&mut self, rng: &mut R, draw_id: u64) -> DrawView {
+        let mut draw = self.draws.get(&draw_id).expect("Draw not found");
+        assert!(!draw.is_revealed(), "Draw is already revealed");
+        let deck = self.internal_deck(&draw.deck_id);
+        draw.cards = rand_distinct(rng, deck.size() as u32, draw.positions.len() as u32)
+            .into_iter()
+            .map(|card_index| DrawnCard {
+                card_index: card_index as u8,
+                orientation: if rand_bool(rng) { Orientation::Upright } else { Orientation::Reversed },
+            })
+            .collect();
+        draw.revealed_at = Some(env::block_height());
+        self.draws.insert(&draw_id, &draw);
+        draw.view(draw_id, &deck)
     }
 
-    //random no generator, upper limit not inclusive
-    pub(crate) fn rand_num(upper_limit: u128) -> u128 {
-        // Here we get a first byte of a random seed
-        let random_seed = *env::random_seed().get(0).unwrap() as u128;
-        return random_seed % upper_limit;
+    //mints the revealed draw `draw_id` for `receiver_id` on behalf of `minter`, the caller charges for it
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn internal_mint(
+        &mut self,
+        minter: &AccountId,
+        receiver_id: AccountId,
+        draw_id: U64,
+        reading: String,
+        question: Option<SealedQuestion>,
+        soulbound: bool,
+        signature: Option<ReadingSignature>,
+        reference: Option<ReadingReference>,
+    ) -> Token {
+        self.config.assert_reading_len(&reading);
+        if let Some(question) = &question {
+            question.assert_valid(&self.config);
+        }
+        if let Some(reference) = &reference {
+            reference.assert_valid();
+        }
+        let draw = self.draws.remove(&draw_id.0).expect("Draw not found");
+        assert!(&draw.drawer == minter || self.has_role(minter, Role::Minter), "Only the drawer or a minter can mint this draw");
+        assert!(draw.is_revealed(), "Draw is not revealed yet");
+        if let Some(signature) = &signature {
+            self.assert_reading_signature(signature, draw_id.0, &draw, &reading);
+        }
+        let verified_by = signature.map(|signature| signature.interpreter_id);
+        let token_id = self.minted;
+        assert!(self.tokens.owner_by_id.get(&token_id.to_string()).is_none(), "Token {} already exists", token_id);
+        self.minted += 1;
+        let deck = self.internal_deck(&draw.deck_id);
+        let extra = draw.layout_json(&deck, question.as_ref().map(|question| &question.hash), soulbound, verified_by.as_ref());
+        let metadata = Self::set_token_metadata(token_id, &draw, &deck, reading, extra, reference);
+        if let Some(title) = &metadata.title {
+            self.config.assert_title_len(title);
+        }
+        let token = self.tokens.internal_mint_with_refund(token_id.to_string(), receiver_id, Some(metadata), None);
+        self.royalties.insert(&token.token_id, &self.royalties_for(&deck));
+        if let Some(question) = question {
+            self.questions.insert(&token.token_id, &question.into());
+        }
+        if soulbound {
+            self.soulbound.insert(&token.token_id);
+        }
+        NftMint { owner_id: &token.owner_id, token_ids: &[&token.token_id], memo: None }.emit();
+        token
     }
 
     //view total minted no
     pub fn get_num(&self) -> String {
-        return self.minted.to_string();
+        self.minted.to_string()
     }
 
-    fn set_token_metadata(token_id: i8, card: String, reading: String, position: String) -> TokenMetadata {
-        let name = format!("Reading#{}: {} in {}", token_id.to_string(), card, position);
-        let i = MAJOR_ARCANA_NAME.iter().position(|&r| r == card);
-        assert!(i != None, "Card not found");
-        let card_index = i.unwrap();
-        let card_uri = format!("{}{}.png", MAJOR_ARCANA_CARD_URI, card_index.to_string());
+    fn set_token_metadata(
+        token_id: u64,
+        draw: &Draw,
+        deck: &Deck,
+        reading: String,
+        extra: String,
+        reference: Option<ReadingReference>,
+    ) -> TokenMetadata {
+        let first = draw.cards[0].card(deck);
+        let name = if draw.cards.len() == 1 {
+            format!("Reading#{}: {} in {}", token_id, first.name, draw.cards[0].orientation.name())
+        } else {
+            format!("Reading#{}: {}", token_id, draw.spread_name)
+        };
         TokenMetadata {
-            title: Some(name.into()),
-            description: Some(reading.into()),
-            media: Some(card_uri.into()),
-            media_hash: None,
+            title: Some(name),
+            description: Some(reading),
+            media: Some(first.media),
+            media_hash: first.media_hash,
             copies: Some(1u64),
-            issued_at: None,
+            issued_at: Some(env::block_timestamp_ms().to_string()),
             expires_at: None,
             starts_at: None,
             updated_at: None,
-            extra: None,
-            reference: None,
-            reference_hash: None,
+            extra: Some(extra),
+            reference: reference.as_ref().map(|reference| reference.reference.clone()),
+            reference_hash: reference.map(|reference| reference.reference_hash),
         }
     }
 }
 
-near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
-near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
 near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
 
 //view metadata
@@ -238,13 +518,26 @@ impl NonFungibleTokenMetadataProvider for Contract {
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
     use near_sdk::testing_env;
-    use std::collections::HashMap;
     use near_sdk::log;
 
+    use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
+    use near_contract_standards::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+    use near_contract_standards::non_fungible_token::bytes_for_approved_account_id;
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::json_types::U128;
+    use near_sdk::PromiseOrValue;
+    use std::convert::TryFrom;
+
     use super::*;
 
+    // enough to cover the storage of a draw or a reading in these tests
+    const STORAGE_DEPOSIT: u128 = 20_000_000_000_000_000_000_000;
+
     fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
         builder
@@ -254,20 +547,71 @@ mod tests {
         builder
     }
 
+    fn draw(context: &mut VMContextBuilder, contract: &mut Contract, drawer: AccountId) -> DrawView {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(drawer)
+            .build());
+        let draw = contract.draw_cards(None, None);
+        reveal(context, contract, draw.draw_id)
+    }
+
+    //runs the `reveal_draw` callback in the next block, the predecessor is restored afterwards
+    fn reveal(context: &mut VMContextBuilder, contract: &mut Contract, draw_id: U64) -> DrawView {
+        let predecessor = context.context.predecessor_account_id.clone();
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .block_index(env::block_height() + 1)
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let draw = contract.reveal_draw(draw_id);
+        context.predecessor_account_id(predecessor);
+        draw
+    }
+
+    fn replay_draw(contract: &mut Contract, seed: [u8; 32], spread_id: &str) -> DrawView {
+        let spread = contract.get_spread(spread_id.to_string()).unwrap();
+        let (draw_id, _) =
+            contract.internal_commit_draw(accounts(0), spread_id.to_string(), &spread, DEFAULT_DECK.to_string(), DRAW_PRICE, None);
+        contract.internal_reveal_draw(&mut SeedStream::new(seed), draw_id)
+    }
+
+    fn mint(context: &mut VMContextBuilder, contract: &mut Contract, owner: AccountId) -> Token {
+        let draw = draw(context, contract, owner.clone());
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(owner.clone())
+            .build());
+        contract.nft_mint(owner, draw.draw_id, "test reading".to_string(), None, None, None, None)
+    }
+
+    //logged `celestial` events of kind `event`
+    fn celestial_events(event: &str) -> Vec<near_sdk::serde_json::Value> {
+        get_logs()
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|json| near_sdk::serde_json::from_str::<near_sdk::serde_json::Value>(json).unwrap())
+            .filter(|value| value["standard"] == "celestial" && value["event"] == event)
+            .collect()
+    }
+
     fn sample_token_metadata() -> TokenMetadata {
-        let name = "Reading#0: 0 The Fool in upright".to_string();
-        let card_uri = format!("{}{}.png", MAJOR_ARCANA_CARD_URI, "0".to_string());
+        let name = "Reading#0: 0 The Fool in reversed".to_string();
+        let card_uri = format!("{}{}.png", deck::MAJOR_ARCANA_CARD_URI, "0");
         TokenMetadata {
-            title: Some(name.into()),
+            title: Some(name),
             description: Some("test reading".into()),
-            media: Some(card_uri.into()),
+            media: Some(card_uri),
             media_hash: None,
             copies: Some(1u64),
-            issued_at: None,
+            issued_at: Some("0".to_string()),
             expires_at: None,
             starts_at: None,
             updated_at: None,
-            extra: None,
+            extra: Some(r#"{"deck":"celestial","spread":"single","block_height":"0","revealed_at":"1","cards":[{"spread_position":"Card","card_index":0,"card":"0 The Fool","card_uri":"ipfs://bafybeifrqo4oorpn2y2l7vy5y4v4tqebvho5q5hg5rfsx2rafzng3u556q/0.png","arcana":"major","suit":null,"orientation":"reversed"}],"question_hash":null,"soulbound":false,"verified_by":null}"#.to_string()),
             reference: None,
             reference_hash: None,
         }
@@ -277,7 +621,7 @@ mod tests {
     fn test_new() {
         let mut context = get_context(accounts(1));
         testing_env!(context.build());
-        let contract = Contract::new_default_meta(accounts(1).into());
+        let contract = Contract::new_default_meta(accounts(1));
         testing_env!(context.is_view(true).build());
         assert_eq!(contract.nft_token("1".to_string()), None);
     }
@@ -292,9 +636,9 @@ mod tests {
     
     #[test]
     fn test_get_num() {
-        let mut context = get_context(accounts(1));
+        let context = get_context(accounts(1));
         testing_env!(context.build());
-        let contract = Contract::new_default_meta(accounts(1).into());
+        let contract = Contract::new_default_meta(accounts(1));
         log!("minted: {}", contract.minted.to_string());
     }
 
@@ -302,17 +646,85 @@ mod tests {
     fn test_draw_success() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST + DRAW_PRICE)
+            .attached_deposit(STORAGE_DEPOSIT + DRAW_PRICE)
             .predecessor_account_id(accounts(0))
             .build());
 
-        let draw = Contract::draw_cards();
-        log!("Card:{}, Position:{}", draw[0], draw[2]);
-        
+        let draw = contract.draw_cards(None, None);
+        assert!(draw.cards.is_empty());
+        assert_eq!(draw.revealed_at, None);
+        assert_eq!(contract.get_draw(draw.draw_id), Some(draw));
+        assert_eq!(calls_to(&accounts(0)), vec!["reveal_draw".to_string()]);
+        assert_eq!(contract.get_treasury().draw_revenue.0, 0);
+
+        let draw = reveal(&mut context, &mut contract, 0.into());
+        log!("Card:{}, Orientation:{}", draw.cards[0].card, draw.cards[0].orientation.name());
+        assert_eq!(draw.revealed_at, Some(1.into()));
+        assert_eq!(contract.get_draw(draw.draw_id), Some(draw));
+        assert_eq!(contract.get_treasury().draw_revenue.0, DRAW_PRICE);
+    }
+
+    #[test]
+    fn test_draw_event() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        let events = celestial_events("card_drawn");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["version"], events::CARD_DRAWN_VERSION);
+        let data = &events[0]["data"][0];
+        assert_eq!(data["drawer"], accounts(1).as_str());
+        assert_eq!(data["draw_id"], "0");
+        assert_eq!((&data["deck_id"], &data["spread_id"]), (&"celestial".into(), &"single".into()));
+        assert_eq!(data["cards"][0]["card"], draw.cards[0].card.as_str());
+        assert_eq!(data["price"], DRAW_PRICE.to_string());
+    }
+
+    #[test]
+    fn test_mint_event() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let token = mint(&mut context, &mut contract, accounts(1));
+        let events = celestial_events("reading_minted");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["version"], events::READING_MINTED_VERSION);
+        let data = &events[0]["data"][0];
+        assert_eq!(data["owner_id"], accounts(1).as_str());
+        assert_eq!(data["token_id"], token.token_id);
+        assert_eq!(data["draw_id"], "0");
+        assert_eq!(data["price"], MINT_PRICE.to_string());
+    }
+
+    #[test]
+    fn test_draw_replays_seed() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        // 250 is rejected for 22 cards, 23 % 22 == 1, 7 is odd
+        let mut seed = [0; 32];
+        seed[..3].copy_from_slice(&[250, 23, 7]);
+        let draw = replay_draw(&mut contract, seed, SINGLE_SPREAD);
+        assert_eq!(draw.cards[0].card, "I The Magician");
+        assert_eq!(draw.cards[0].orientation, Orientation::Upright);
+
+        // 60 is below the 242 rejection zone for 22 cards, 60 % 22 == 16
+        seed[..2].copy_from_slice(&[60, 0]);
+        let draw = replay_draw(&mut contract, seed, SINGLE_SPREAD);
+        assert_eq!((draw.cards[0].card_index, draw.cards[0].card.as_str()), (16, "XVI The Tower"));
+        assert_eq!(draw.cards[0].orientation, Orientation::Reversed);
+
+        let first = replay_draw(&mut contract, [9; 32], "celtic_cross");
+        let second = replay_draw(&mut contract, [9; 32], "celtic_cross");
+        assert_eq!(first.cards, second.cards);
     }
 
     #[test]
@@ -320,214 +732,2114 @@ mod tests {
     fn test_draw_failure() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST )
+            .attached_deposit(STORAGE_DEPOSIT )
             .predecessor_account_id(accounts(0))
             .build());
-        let draw = Contract::draw_cards();
+        contract.draw_cards(None, None);
     }
 
     #[test]
     fn test_mint() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
+        let draw = draw(&mut context, &mut contract, accounts(0));
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST + MINT_PRICE)
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
             .predecessor_account_id(accounts(0))
             .build());
         
-        let card = "0 The Fool".to_string();
         let reading = "test reading".to_string();
-        let question = "test question".to_string();
-        let position = "upright".to_string();
-        let token = contract.nft_mint(accounts(0), card, reading, question, position);
+        let token = contract.nft_mint(accounts(0), draw.draw_id, reading, None, None, None, None);
         assert_eq!(token.owner_id.to_string(), accounts(0).to_string());
         assert_eq!(token.metadata.unwrap(), sample_token_metadata());
         assert_eq!(token.approved_account_ids.unwrap(), HashMap::new());
     }
 
+    fn sealed_question(question: &str, salt: &[u8]) -> SealedQuestion {
+        SealedQuestion {
+            hash: env::sha256(&[salt, question.as_bytes()].concat()).into(),
+            ciphertext: Some(b"sealed for the owner".to_vec().into()),
+        }
+    }
+
+    fn mint_with_question(context: &mut VMContextBuilder, contract: &mut Contract, question: SealedQuestion) -> Token {
+        let draw = draw(context, contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), Some(question), None, None, None)
+    }
+
     #[test]
-    fn test_mint_storage_calc() {
+    fn test_reveal_question() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let sealed = sealed_question("test question", b"salt");
+        let token = mint_with_question(&mut context, &mut contract, sealed.clone());
+        assert_eq!(contract.get_question(token.token_id.clone()), Some(sealed.clone().into()));
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT).build());
+        contract.reveal_question(token.token_id.clone(), "test question".to_string(), b"salt".to_vec().into());
+        let question = contract.get_question(token.token_id).unwrap();
+        assert_eq!(question.revealed.unwrap(), "test question");
+        assert_eq!((question.hash, question.ciphertext), (sealed.hash, None));
+        assert_eq!(refunds_to(&accounts(1)).len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Question does not match its hash")]
+    fn test_reveal_wrong_question() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint_with_question(&mut context, &mut contract, sealed_question("test question", b"salt"));
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT).build());
+        contract.reveal_question(token.token_id, "test question".to_string(), b"pepper".to_vec().into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner can reveal its question")]
+    fn test_reveal_question_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint_with_question(&mut context, &mut contract, sealed_question("test question", b"salt"));
+
+        testing_env!(context.predecessor_account_id(accounts(2)).attached_deposit(STORAGE_DEPOSIT).build());
+        contract.reveal_question(token.token_id, "test question".to_string(), b"salt".to_vec().into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Question hash must be a 32-byte sha256 hash")]
+    fn test_mint_invalid_question() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        mint_with_question(
+            &mut context,
+            &mut contract,
+            SealedQuestion { hash: b"test question".to_vec().into(), ciphertext: None },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Draw not found")]
+    fn test_mint_without_draw() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST + MINT_PRICE)
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
             .predecessor_account_id(accounts(0))
             .build());
-        
-        let card = "0 The Fool".to_string();
-        let reading = "The hierophant in reverse suggests there may be non-traditional methods and unconventional approaches involved in your negotiation with your business partner. Don’t feel boxed in by the norm or what has been successful in the past. This could lead to arguments or impasses, so be patient. Respect their viewpoint while expressing yours openly. Remember, productive dialogue involves understanding and compromises. This doesn’t mean you must abandon your beliefs, but adapting to change is crucial in this situation. Be flexible and remember your mutual goals to create a win-win situation.".to_string();
-        let question = "test question".to_string();
-        let position = "upright".to_string();
-        contract.nft_mint(accounts(0), card, reading, question, position);
-        log!("storage cost: {} yoctoNear", env::storage_byte_cost()*env::storage_usage()as u128)
+        contract.nft_mint(accounts(0), U64(0), "test reading".to_string(), None, None, None, None);
     }
 
     #[test]
-    fn test_transfer() {
+    #[should_panic(expected = "Only the drawer or a minter can mint this draw")]
+    fn test_mint_someone_elses_draw() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
+        let draw = draw(&mut context, &mut contract, accounts(1));
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST + MINT_PRICE)
-            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(2))
             .build());
-        
-            let card = "0 The Fool".to_string();
-            let reading = "test reading".to_string();
-            let question = "test question".to_string();
-            let position = "upright".to_string();
-            let token = contract.nft_mint(accounts(0), card, reading, question, position);
+        contract.nft_mint(accounts(2), draw.draw_id, "test reading".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Draw not found")]
+    fn test_mint_draw_twice() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
+        let draw = draw(&mut context, &mut contract, accounts(0));
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(1)
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
             .predecessor_account_id(accounts(0))
             .build());
-        contract.nft_transfer(accounts(1), token.token_id.clone(), None, None);
+        contract.nft_mint(accounts(0), draw.draw_id, "test reading".to_string(), None, None, None, None);
+        assert_eq!(contract.get_draw(draw.draw_id), None);
 
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .account_balance(env::account_balance())
-            .is_view(true)
-            .attached_deposit(0)
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(0))
             .build());
-        if let Some(token) = contract.nft_token(token.token_id.clone()) {
-            assert_eq!(token.owner_id.to_string(), accounts(1).to_string());
-            assert_eq!(token.metadata.unwrap(), sample_token_metadata());
-            assert_eq!(token.approved_account_ids.unwrap(), HashMap::new());
-        } else {
-            panic!("token not correctly created, or not found by nft_token");
-        }
+        contract.nft_mint(accounts(0), draw.draw_id, "test reading".to_string(), None, None, None, None);
     }
 
     #[test]
-    fn test_approve() {
+    #[should_panic(expected = "Token 0 already exists")]
+    fn test_mint_existing_token_id() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
+        let draw = draw(&mut context, &mut contract, accounts(0));
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST + MINT_PRICE)
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
             .predecessor_account_id(accounts(0))
             .build());
-                
-        let card = "0 The Fool".to_string();
-        let reading = "test reading".to_string();
-        let question = "test question".to_string();
-        let position = "upright".to_string();
-        let token = contract.nft_mint(accounts(0), card, reading, question, position);
+        contract.nft_mint(accounts(0), draw.draw_id, "test reading".to_string(), None, None, None, None);
 
-        // alice approves bob
+        contract.minted = 0;
+        let draw = self::draw(&mut context, &mut contract, accounts(0));
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(150000000000000000000)
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
             .predecessor_account_id(accounts(0))
             .build());
-        contract.nft_approve(token.token_id.clone(), accounts(1), None);
+        contract.nft_mint(accounts(0), draw.draw_id, "test reading".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    fn test_migrate_from_v0() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut tokens = NonFungibleToken::new(
+            StorageKey::NonFungibleToken,
+            accounts(0),
+            Some(StorageKey::TokenMetadata),
+            Some(StorageKey::Enumeration),
+            Some(StorageKey::Approval),
+        );
+        tokens.internal_mint_with_refund("0".to_string(), accounts(1), Some(sample_token_metadata()), None);
+        let metadata = LazyOption::new(StorageKey::Metadata, Some(&NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "Celestial NFT".to_string(),
+            symbol: "CT".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }));
+        env::state_write(&migrate::ContractV0 { tokens, metadata, minted: 127 });
+
+        let mut contract = Contract::migrate();
+        assert_eq!(contract.get_num(), "127");
+        assert_eq!(contract.nft_token("0".to_string()).unwrap().owner_id, accounts(1));
+        //every field added before the first versioned release starts empty
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert_eq!(contract.get_pending_owner(), None);
+        assert!(contract.get_roles(accounts(0)).is_empty());
+        assert_eq!(contract.get_paused(), PauseState::default());
+        assert!(contract.get_ft_prices().is_empty());
+        assert!(contract.get_interpreters().is_empty());
+        assert_eq!(contract.get_annotation_count("0".to_string()), U64(0));
+        assert_eq!(contract.get_decks().len(), 1);
 
+        let draw = draw(&mut context, &mut contract, accounts(1));
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .account_balance(env::account_balance())
-            .is_view(true)
-            .attached_deposit(0)
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
             .build());
-        assert!(contract.nft_is_approved(token.token_id.clone(), accounts(1), Some(1)));
+        let token = contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+        assert_eq!(contract.get_state_version(), StateVersion::V1);
+        assert_eq!(token.token_id, "127");
+        assert_eq!(contract.get_num(), "128");
     }
 
     #[test]
-    fn test_revoke() {
+    fn test_migrate_current_state() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        assert_eq!(contract.get_state_version(), StateVersion::V1);
+        let token = mint(&mut context, &mut contract, accounts(1));
+        env::state_write(&contract);
 
-        testing_env!(context
-            .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST + MINT_PRICE)
-            .predecessor_account_id(accounts(0))
-            .build());
-           
-        let card = "0 The Fool".to_string();
-        let reading = "test reading".to_string();
-        let question = "test question".to_string();
-        let position = "upright".to_string();
-        let token = contract.nft_mint(accounts(0), card, reading, question, position);
+        testing_env!(context.predecessor_account_id(accounts(0)).attached_deposit(0).build());
+        let contract = Contract::migrate();
+        assert_eq!(contract.get_state_version(), StateVersion::V1);
+        assert_eq!(contract.nft_token(token.token_id).unwrap().owner_id, accounts(1));
+        assert_eq!(contract.get_num(), "1");
+    }
 
-        // alice approves bob
+    #[test]
+    #[should_panic(expected = "Only the contract owner can call this method")]
+    fn test_migrate_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = Contract::new_default_meta(accounts(0));
+        env::state_write(&contract);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        Contract::migrate();
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = Contract::new_default_meta(accounts(0));
+
+        let mut vm_context = context.build();
+        vm_context.input = b"\0asm new code".to_vec();
+        testing_env!(vm_context);
+        contract.upgrade();
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(0));
+        assert!(matches!(&receipts[0].actions[0], VmAction::DeployContract { code } if code == b"\0asm new code"));
+        assert!(matches!(&receipts[0].actions[1], VmAction::FunctionCall { function_name, .. } if function_name == "migrate"));
+    }
+
+    #[test]
+    fn test_upgrade_large_code() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = Contract::new_default_meta(accounts(0));
+
+        let mut vm_context = context.build();
+        vm_context.input = vec![0; 400_000];
+        vm_context.prepaid_gas = Gas(300_000_000_000_000);
+        testing_env!(vm_context);
+        contract.upgrade();
+        let receipts = get_created_receipts();
+        let migrate_gas = match &receipts[0].actions[1] {
+            VmAction::FunctionCall { gas, .. } => *gas,
+            _ => panic!("Expected a migrate call"),
+        };
+        //the migrate call gets the gas left once the deploy fee is paid, its gas counts as used
+        assert!(env::used_gas() <= Gas(300_000_000_000_000));
+        assert!(migrate_gas > Gas(200_000_000_000_000), "{:?}", migrate_gas);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner can call this method")]
+    fn test_upgrade_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = Contract::new_default_meta(accounts(0));
+
+        let mut vm_context = context.predecessor_account_id(accounts(2)).build();
+        vm_context.input = b"\0asm new code".to_vec();
+        testing_env!(vm_context);
+        contract.upgrade();
+    }
+
+    fn refunds_to(account_id: &AccountId) -> Vec<Balance> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| &receipt.receiver_id == account_id)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                VmAction::Transfer { deposit } => Some(deposit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_draw_refunds_overpayment() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let deposit = DRAW_PRICE + 1_000_000_000_000_000_000_000_000;
+        let initial_storage_usage = env::storage_usage();
         testing_env!(context
-            .storage_usage(env::storage_usage())
-            .attached_deposit(150000000000000000000)
-            .predecessor_account_id(accounts(0))
+            .storage_usage(initial_storage_usage)
+            .attached_deposit(deposit)
+            .predecessor_account_id(accounts(1))
             .build());
-        contract.nft_approve(token.token_id.clone(), accounts(1), None);
+        contract.draw_cards(None, None);
+        let storage_used = env::storage_usage() - initial_storage_usage + contract.draws.get(&0).unwrap().reveal_storage_bytes();
+        let storage_cost = env::storage_byte_cost() * Balance::from(storage_used);
+        assert!(storage_cost > 0);
+        assert_eq!(refunds_to(&accounts(1)), vec![deposit - DRAW_PRICE - storage_cost]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal to the draw price + storage")]
+    fn test_draw_without_storage() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
-        // alice revokes bob
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(1)
-            .predecessor_account_id(accounts(0))
+            .attached_deposit(DRAW_PRICE)
+            .predecessor_account_id(accounts(1))
             .build());
-        contract.nft_revoke(token.token_id.clone(), accounts(1));
+        contract.draw_cards(None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Draw cannot be revealed in the block it was committed in")]
+    fn test_reveal_in_commit_block() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .account_balance(env::account_balance())
-            .is_view(true)
-            .attached_deposit(0)
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
             .build());
-        assert!(!contract.nft_is_approved(token.token_id.clone(), accounts(1), None));
+        let draw = contract.draw_cards(None, None);
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(0)).build());
+        contract.reveal_draw(draw.draw_id);
     }
 
     #[test]
-    fn test_revoke_all() {
+    #[should_panic(expected = "Draw is not revealed yet")]
+    fn test_mint_unrevealed_draw() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut contract = Contract::new_default_meta(accounts(0));
 
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST + MINT_PRICE)
-            .predecessor_account_id(accounts(0))
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
             .build());
-                    
-        let card = "0 The Fool".to_string();
-        let reading = "test reading".to_string();
-        let question = "test question".to_string();
-        let position = "upright".to_string();
-        let token = contract.nft_mint(accounts(0), card, reading, question, position);
+        let draw = contract.draw_cards(None, None);
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT + MINT_PRICE).build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+    }
 
-        // alice approves bob
+    #[test]
+    fn test_refund_draw() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let initial_storage_usage = env::storage_usage();
         testing_env!(context
-            .storage_usage(env::storage_usage())
-            .attached_deposit(150000000000000000000)
-            .predecessor_account_id(accounts(0))
+            .storage_usage(initial_storage_usage)
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
             .build());
-        contract.nft_approve(token.token_id.clone(), accounts(1), None);
+        let draw = contract.draw_cards(None, None);
+        let storage_cost = env::storage_byte_cost()
+            * Balance::from(env::storage_usage() - initial_storage_usage + contract.draws.get(&0).unwrap().reveal_storage_bytes());
 
-        // alice revokes bob
+        // the reveal never ran
         testing_env!(context
             .storage_usage(env::storage_usage())
+            .block_index(DRAW_REVEAL_TIMEOUT + 1)
             .attached_deposit(1)
-            .predecessor_account_id(accounts(0))
             .build());
-        contract.nft_revoke_all(token.token_id.clone());
+        contract.refund_draw(draw.draw_id);
+        assert_eq!(refunds_to(&accounts(1)), vec![DRAW_PRICE + storage_cost + 1]);
+        assert_eq!(contract.get_draw(draw.draw_id), None);
+        assert_eq!(contract.get_treasury().draw_revenue.0, 0);
+        let events = celestial_events("draw_refunded");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["data"][0]["drawer"], accounts(1).as_str());
+        assert_eq!(events[0]["data"][0]["amount"], DRAW_PRICE.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Draw can only be refunded after block 600")]
+    fn test_refund_draw_before_timeout() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .account_balance(env::account_balance())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let draw = contract.draw_cards(None, None);
+        testing_env!(context.block_index(DRAW_REVEAL_TIMEOUT).attached_deposit(1).build());
+        contract.refund_draw(draw.draw_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Draw is already revealed")]
+    fn test_refund_revealed_draw() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        testing_env!(context.block_index(DRAW_REVEAL_TIMEOUT + 2).attached_deposit(1).build());
+        contract.refund_draw(draw.draw_id);
+    }
+
+    #[test]
+    fn test_mint_refunds_overpayment() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let draw = draw(&mut context, &mut contract, accounts(1));
+
+        let deposit = MINT_PRICE + 1_000_000_000_000_000_000_000_000;
+        let initial_storage_usage = env::storage_usage();
+        testing_env!(context
+            .storage_usage(initial_storage_usage)
+            .attached_deposit(deposit)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+        let storage_cost = env::storage_byte_cost() * Balance::from(env::storage_usage() - initial_storage_usage);
+        assert!(storage_cost > 0);
+        assert_eq!(refunds_to(&accounts(1)), vec![deposit - MINT_PRICE - storage_cost]);
+        assert_eq!(contract.get_treasury().mint_revenue.0, MINT_PRICE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal to the mint price + storage")]
+    fn test_mint_without_storage() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let draw = draw(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal to the mint price: 1000000000000000000000000")]
+    fn test_mint_underpaid() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let draw = draw(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_PRICE - 1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    fn test_draw_spread() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE * 10 + 5 * STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let draw = contract.draw_cards(Some("celtic_cross".to_string()), None);
+        let draw = reveal(&mut context, &mut contract, draw.draw_id);
+        assert_eq!(draw.spread_id, "celtic_cross");
+        assert_eq!(draw.cards.len(), 10);
+        assert_eq!(draw.cards[0].label, "Present");
+        assert_eq!(draw.cards[9].label, "Outcome");
+        let mut card_indices: Vec<u8> = draw.cards.iter().map(|card| card.card_index).collect();
+        card_indices.sort_unstable();
+        card_indices.dedup();
+        assert_eq!(card_indices.len(), 10);
+        assert_eq!(contract.get_treasury().draw_revenue.0, DRAW_PRICE * 10);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(5 * STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let token = contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+        let metadata = token.metadata.unwrap();
+        assert_eq!(metadata.title.unwrap(), "Reading#0: Celtic Cross");
+        assert_eq!(metadata.media.unwrap(), draw.cards[0].card_uri);
+        let layout: near_sdk::serde_json::Value = near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(layout["spread"], "celtic_cross");
+        assert_eq!(layout["cards"][3]["spread_position"], "Recent Past");
+        assert_eq!(layout["cards"][3]["card"], draw.cards[3].card.as_str());
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal to the draw price: 1500000000000000000000000")]
+    fn test_draw_spread_underpaid() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.draw_cards(Some("three_card".to_string()), None);
+    }
+
+    #[test]
+    fn test_custom_spread() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let spread = Spread {
+            name: "Crossroads".to_string(),
+            positions: vec!["Path A".to_string(), "Path B".to_string()],
+            price: Some(U128(DRAW_PRICE)),
+        };
+        contract.set_spread("crossroads".to_string(), spread.clone());
+        assert_eq!(contract.get_spread("crossroads".to_string()), Some(spread));
+        assert_eq!(contract.get_spreads().len(), 4);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let draw = contract.draw_cards(Some("crossroads".to_string()), None);
+        let draw = reveal(&mut context, &mut contract, draw.draw_id);
+        assert_eq!(draw.cards.len(), 2);
+        assert_ne!(draw.cards[0].card_index, draw.cards[1].card_index);
+
+        // a pending draw survives the removal of its spread
+        testing_env!(context.predecessor_account_id(accounts(0)).attached_deposit(0).build());
+        contract.remove_spread("crossroads".to_string());
+        assert_eq!(contract.get_spread("crossroads".to_string()), None);
+        assert_eq!(contract.get_draw(draw.draw_id).unwrap().cards, draw.cards);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner or an account with the admin role can call this method")]
+    fn test_set_spread_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_spread(
+            "mine".to_string(),
+            Spread { name: "Mine".to_string(), positions: vec!["Card".to_string()], price: Some(U128(1)) },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Spread not found")]
+    fn test_draw_unknown_spread() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.draw_cards(Some("horseshoe".to_string()), None);
+    }
+
+    fn sample_deck() -> Deck {
+        Deck {
+            name: "Solstice".to_string(),
+            base_uri: "ipfs://solstice/".to_string(),
+            cards: (0..deck::MAJOR_ARCANA_COUNT)
+                .map(|i| DeckCard {
+                    name: format!("Solstice {}", i),
+                    media: format!("{}.webp", i),
+                    media_hash: Some(env::sha256(i.to_string().as_bytes()).into()),
+                })
+                .collect(),
+            artist_id: None,
+            artist_royalty: 0,
+        }
+    }
+
+    //`sample_deck` with the Minor Arcana
+    fn full_sample_deck() -> Deck {
+        let mut deck = sample_deck();
+        deck.cards.extend((deck::MAJOR_ARCANA_COUNT..deck::DECK_SIZE).map(|i| DeckCard {
+            name: format!("Solstice {}", i),
+            media: format!("{}.webp", i),
+            media_hash: None,
+        }));
+        deck
+    }
+
+    #[test]
+    fn test_custom_deck() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_deck("solstice".to_string(), sample_deck());
+        assert_eq!(contract.get_deck("solstice".to_string()), Some(sample_deck()));
+        assert_eq!(
+            contract.get_decks(),
+            vec![("celestial".to_string(), "Celestial".to_string(), 22), ("solstice".to_string(), "Solstice".to_string(), 22)]
+        );
+        let card = contract.get_card(3, Some("solstice".to_string()));
+        assert_eq!((card.name.as_str(), card.media.as_str()), ("Solstice 3", "ipfs://solstice/3.webp"));
+        assert_eq!(card.element, deck::Element::Earth);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let draw = contract.draw_cards(None, Some("solstice".to_string()));
+        let draw = reveal(&mut context, &mut contract, draw.draw_id);
+        assert_eq!(draw.deck_id, "solstice");
+        let card_index = draw.cards[0].card_index;
+        assert!((card_index as usize) < deck::MAJOR_ARCANA_COUNT);
+        assert_eq!(draw.cards[0].card, format!("Solstice {}", card_index));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let token = contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+        let metadata = token.metadata.unwrap();
+        assert_eq!(metadata.media.unwrap(), format!("ipfs://solstice/{}.webp", card_index));
+        assert_eq!(metadata.media_hash.unwrap().0, env::sha256(card_index.to_string().as_bytes()));
+        let layout: near_sdk::serde_json::Value = near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(layout["deck"], "solstice");
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner or an account with the admin role can call this method")]
+    fn test_set_deck_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_deck("solstice".to_string(), sample_deck());
+    }
+
+    #[test]
+    fn test_deck_gains_minor_arcana() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let spread = contract.get_spread(SINGLE_SPREAD.to_string()).unwrap();
+        let (draw_id, _) =
+            contract.internal_commit_draw(accounts(0), SINGLE_SPREAD.to_string(), &spread, DEFAULT_DECK.to_string(), DRAW_PRICE, None);
+
+        contract.set_deck(DEFAULT_DECK.to_string(), full_sample_deck());
+        assert_eq!(contract.get_decks(), vec![("celestial".to_string(), "Solstice".to_string(), 78)]);
+        // 60 is below the 234 rejection zone for 78 cards
+        let mut seed = [0; 32];
+        seed[..2].copy_from_slice(&[60, 0]);
+        contract.internal_reveal_draw(&mut SeedStream::new(seed), draw_id);
+        let draw = contract.get_draw(U64(draw_id)).unwrap();
+        assert_eq!((draw.cards[0].card_index, draw.cards[0].card.as_str()), (60, "Solstice 60"));
+    }
+
+    #[test]
+    #[should_panic(expected = "A registered deck cannot lose cards")]
+    fn test_deck_cannot_shrink() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_deck("solstice".to_string(), full_sample_deck());
+        contract.set_deck("solstice".to_string(), sample_deck());
+    }
+
+    #[test]
+    #[should_panic(expected = "Deck not found")]
+    fn test_draw_unknown_deck() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.draw_cards(None, Some("solstice".to_string()));
+    }
+
+    fn payout(entries: &[(AccountId, Balance)]) -> Payout {
+        Payout { payout: entries.iter().map(|(account_id, amount)| (account_id.clone(), U128(*amount))).collect() }
+    }
+
+    #[test]
+    fn test_payout() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint(&mut context, &mut contract, accounts(1));
+
+        let vault_id = contract.get_config().vault_id;
+        assert_eq!(
+            contract.nft_payout(token.token_id.clone(), U128(10_000), Some(2)),
+            payout(&[(vault_id.clone(), 500), (accounts(1), 9_500)])
+        );
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        let sale = contract.nft_transfer_payout(accounts(2), token.token_id.clone(), None, None, U128(1_001), None);
+        assert_eq!(sale, payout(&[(vault_id, 50), (accounts(1), 951)]));
+        assert_eq!(contract.nft_token(token.token_id).unwrap().owner_id, accounts(2));
+    }
+
+    #[test]
+    fn test_deck_artist_royalty() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let deck = Deck { artist_id: Some(accounts(3)), artist_royalty: 1_000, ..sample_deck() };
+        contract.set_deck("solstice".to_string(), deck);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let draw = contract.draw_cards(None, Some("solstice".to_string()));
+        reveal(&mut context, &mut contract, draw.draw_id);
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let token = contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+
+        // a later royalty change does not affect minted readings
+        testing_env!(context.predecessor_account_id(accounts(0)).attached_deposit(0).build());
+        contract.set_config(Config { vault_royalty: 0, ..Config::default() });
+        assert_eq!(
+            contract.nft_payout(token.token_id, U128(10_000), None),
+            payout(&[(contract.get_config().vault_id, 500), (accounts(3), 1_000), (accounts(1), 8_500)])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Payout has 2 recipients, more than max_len_payout 1")]
+    fn test_payout_too_long() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint(&mut context, &mut contract, accounts(1));
+        contract.nft_payout(token.token_id, U128(10_000), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Total royalty must be at most 5000 basis points")]
+    fn test_royalty_cap() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let deck = Deck { artist_id: Some(accounts(3)), artist_royalty: 4_600, ..sample_deck() };
+        contract.set_deck("solstice".to_string(), deck);
+    }
+
+    #[test]
+    fn test_revenue_ledger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        mint(&mut context, &mut contract, accounts(1));
+        draw(&mut context, &mut contract, accounts(2));
+        let treasury = contract.get_treasury();
+        assert_eq!(treasury.draw_revenue.0, 2 * DRAW_PRICE);
+        assert_eq!(treasury.mint_revenue.0, MINT_PRICE);
+        assert_eq!(treasury.accrued.0, 2 * DRAW_PRICE + MINT_PRICE);
+        assert_eq!(treasury.available.0, 2 * DRAW_PRICE + MINT_PRICE);
+        assert_eq!(treasury.withdrawn.0, 0);
+    }
+
+    #[test]
+    fn test_withdraw() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        mint(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.withdraw(Some(U128(DRAW_PRICE)));
+        let treasury = contract.get_treasury();
+        assert_eq!(treasury.withdrawn.0, DRAW_PRICE);
+        assert_eq!(treasury.available.0, MINT_PRICE);
+
+        contract.withdraw(None);
+        assert_eq!(contract.get_treasury().available.0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner can call this method")]
+    fn test_withdraw_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        mint(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.withdraw(None);
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+        contract.grant_role(accounts(1), Role::Admin);
+        contract.grant_role(accounts(2), Role::Minter);
+        assert_eq!(contract.get_roles(accounts(1)), vec![Role::Admin]);
+        assert_eq!(contract.get_role_members(Role::Minter), vec![accounts(2)]);
+
+        // an admin manages the catalogue
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_spread(
+            "mine".to_string(),
+            Spread { name: "Mine".to_string(), positions: vec!["Card".to_string()], price: None },
+        );
+        assert!(contract.get_spread("mine".to_string()).is_some());
+
+        // a minter mints the draw of someone else
+        let draw = draw(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(2))
+            .build());
+        let token = contract.nft_mint(accounts(3), draw.draw_id, "test reading".to_string(), None, None, None, None);
+        assert_eq!(token.owner_id, accounts(3));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).attached_deposit(0).build());
+        contract.revoke_role(accounts(1), Role::Admin);
+        assert!(contract.get_roles(accounts(1)).is_empty());
+        assert!(contract.get_role_members(Role::Admin).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner can call this method")]
+    fn test_admin_cannot_withdraw() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+        mint(&mut context, &mut contract, accounts(2));
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.withdraw(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner can call this method")]
+    fn test_grant_role_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+
+        // admins cannot grant roles either
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.grant_role(accounts(2), Role::Admin);
+    }
+
+    #[test]
+    fn test_transfer_ownership() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.transfer_ownership(Some(accounts(1)));
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert_eq!(contract.get_pending_owner(), Some(accounts(1)));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.accept_ownership();
+        assert_eq!(contract.get_owner(), accounts(1));
+        assert_eq!(contract.get_pending_owner(), None);
+        contract.grant_role(accounts(2), Role::Pauser);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept the ownership")]
+    fn test_accept_withdrawn_ownership() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.transfer_ownership(Some(accounts(1)));
+        contract.transfer_ownership(None);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.accept_ownership();
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Pauser);
+        assert_eq!(contract.get_paused(), PauseState::default());
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let paused = PauseState { draws: true, mints: false, transfers: true };
+        contract.set_paused(paused);
+        assert_eq!(contract.get_paused(), paused);
+        let events = celestial_events("pause_updated");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["data"][0]["updated_by"], accounts(1).to_string());
+        assert_eq!(events[0]["data"][0]["paused"]["draws"], true);
+        assert_eq!(events[0]["data"][0]["paused"]["mints"], false);
+
+        // lifting the flags reopens draws and transfers
+        contract.set_paused(PauseState::default());
+        let token = mint(&mut context, &mut contract, accounts(2));
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(2)).build());
+        contract.nft_transfer(accounts(3), token.token_id.clone(), None, None);
+        assert_eq!(contract.nft_token(token.token_id).unwrap().owner_id, accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Draws are paused")]
+    fn test_draw_when_paused() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_paused(PauseState { draws: true, ..Default::default() });
+        draw(&mut context, &mut contract, accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Mints are paused")]
+    fn test_mint_when_paused() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let draw = draw(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).attached_deposit(0).build());
+        contract.set_paused(PauseState { mints: true, ..Default::default() });
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers are paused")]
+    fn test_transfer_when_paused() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(0)).attached_deposit(0).build());
+        contract.set_paused(PauseState { transfers: true, ..Default::default() });
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.nft_transfer(accounts(2), token.token_id, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner or an account with the pauser role can call this method")]
+    fn test_set_paused_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_paused(PauseState { draws: true, ..Default::default() });
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot withdraw more than the available")]
+    fn test_withdraw_more_than_available() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        draw(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.withdraw(Some(U128(DRAW_PRICE + 1)));
+    }
+
+    #[test]
+    fn test_failed_withdraw_is_restored() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        draw(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.withdraw(None);
+        assert_eq!(contract.get_treasury().available.0, 0);
+
+        testing_env!(
+            context.attached_deposit(0).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![near_sdk::PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_withdraw(contract.get_config().vault_id, U128(DRAW_PRICE)));
+        assert_eq!(contract.get_treasury().available.0, DRAW_PRICE);
+        assert!(celestial_events("funds_withdrawn").is_empty());
+    }
+
+    #[test]
+    fn test_withdraw_event() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(
+            context.build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![near_sdk::PromiseResult::Successful(vec![])],
+        );
+        assert!(contract.resolve_withdraw(accounts(3), U128(DRAW_PRICE)));
+        let events = celestial_events("funds_withdrawn");
+        assert_eq!(events[0]["version"], events::FUNDS_WITHDRAWN_VERSION);
+        assert_eq!(events[0]["data"][0]["vault_id"], accounts(3).as_str());
+        assert_eq!(events[0]["data"][0]["amount"], DRAW_PRICE.to_string());
+    }
+
+    #[test]
+    fn test_set_config() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        assert_eq!(contract.get_config(), Config::default());
+
+        let config = Config { draw_price: U128(DRAW_PRICE * 2), vault_id: accounts(3), ..Config::default() };
+        contract.set_config(config.clone());
+        assert_eq!(contract.get_config(), config);
+        assert!(get_logs()[0].starts_with(r#"EVENT_JSON:{"standard":"celestial","version":"1.0.0","event":"config_updated""#));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE * 2 + STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let draw = contract.draw_cards(None, None);
+        reveal(&mut context, &mut contract, draw.draw_id);
+        assert_eq!(contract.get_treasury().draw_revenue.0, DRAW_PRICE * 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal to the draw price: 1000000000000000000000000")]
+    fn test_draw_after_price_change() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_config(Config { draw_price: U128(DRAW_PRICE * 2), ..Config::default() });
+
+        draw(&mut context, &mut contract, accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner or an account with the admin role can call this method")]
+    fn test_set_config_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_config(Config::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Mint price must be greater than 0")]
+    fn test_set_invalid_config() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_config(Config { mint_price: U128(0), ..Config::default() });
+    }
+
+    #[test]
+    fn test_mint_storage_calc() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let draw = draw(&mut context, &mut contract, accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(0))
+            .build());
+        
+        let reading = "The hierophant in reverse suggests there may be non-traditional methods and unconventional approaches involved in your negotiation with your business partner. Don’t feel boxed in by the norm or what has been successful in the past. This could lead to arguments or impasses, so be patient. Respect their viewpoint while expressing yours openly. Remember, productive dialogue involves understanding and compromises. This doesn’t mean you must abandon your beliefs, but adapting to change is crucial in this situation. Be flexible and remember your mutual goals to create a win-win situation.".to_string();
+        contract.nft_mint(accounts(0), draw.draw_id, reading, None, None, None, None);
+        log!("storage cost: {} yoctoNear", env::storage_byte_cost()*env::storage_usage()as u128)
+    }
+
+    fn storage_deposit(context: &mut VMContextBuilder, contract: &mut Contract, account_id: AccountId, amount: Balance) {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(amount)
+            .predecessor_account_id(account_id)
+            .build());
+        contract.storage_deposit(None, None);
+    }
+
+    #[test]
+    fn test_storage_deposit() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let min = contract.storage_balance_bounds().min.0;
+        assert_eq!(min, env::storage_byte_cost() * Balance::from(storage::STORAGE_ACCOUNT_BYTES));
+        assert!(contract.storage_balance_of(accounts(1)).is_none());
+
+        storage_deposit(&mut context, &mut contract, accounts(1), min + 10);
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!((balance.total.0, balance.available.0), (min + 10, 10));
+
+        // registering again only refunds the deposit
+        testing_env!(context.attached_deposit(min).build());
+        contract.storage_deposit(None, Some(true));
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total.0, min + 10);
+        assert_eq!(refunds_to(&accounts(1)), vec![min]);
+
+        testing_env!(context.attached_deposit(1).build());
+        let balance = contract.storage_withdraw(Some(U128(4)));
+        assert_eq!((balance.total.0, balance.available.0), (min + 6, 6));
+        assert_eq!(refunds_to(&accounts(1)), vec![4]);
+
+        testing_env!(context.attached_deposit(1).build());
+        assert!(contract.storage_unregister(None));
+        assert!(contract.storage_balance_of(accounts(1)).is_none());
+        assert_eq!(refunds_to(&accounts(1)), vec![min + 6]);
+        assert!(!contract.storage_unregister(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "The attached deposit is less than the minimum storage balance")]
+    fn test_storage_deposit_below_min() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount is greater than the available storage balance")]
+    fn test_storage_withdraw_locked_balance() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min + 10);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.storage_withdraw(Some(U128(11)));
+    }
+
+    #[test]
+    fn test_prepaid_storage() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min + STORAGE_DEPOSIT);
+
+        // only the prices are attached, storage comes from the storage balance
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(DRAW_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let draw = contract.draw_cards(None, None);
+        reveal(&mut context, &mut contract, draw.draw_id);
+        let after_draw = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+        assert!(after_draw < STORAGE_DEPOSIT);
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(MINT_PRICE).build());
+        let question = SealedQuestion { hash: env::sha256(b"test question").into(), ciphertext: Some(vec![7; 512].into()) };
+        let token = contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), Some(question), None, None, None);
+        let after_mint = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+        assert!(after_mint < after_draw);
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+        contract.nft_approve(token.token_id, accounts(2), None);
+        let after_approve = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+        assert_eq!(after_mint - after_approve, env::storage_byte_cost() * Balance::from(bytes_for_approved_account_id(&accounts(2))));
+        assert_eq!(refunds_to(&accounts(1)), vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal to the mint price + storage")]
+    fn test_prepaid_storage_exhausted() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min);
+
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let draw = draw(&mut context, &mut contract, accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(0))
+            .build());
+        
+        let reading = "test reading".to_string();
+        let token = contract.nft_mint(accounts(0), draw.draw_id, reading, None, None, None, None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_transfer(accounts(1), token.token_id.clone(), None, None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        if let Some(token) = contract.nft_token(token.token_id.clone()) {
+            assert_eq!(token.owner_id.to_string(), accounts(1).to_string());
+            assert_eq!(token.metadata.unwrap(), sample_token_metadata());
+            assert_eq!(token.approved_account_ids.unwrap(), HashMap::new());
+        } else {
+            panic!("token not correctly created, or not found by nft_token");
+        }
+    }
+
+    #[test]
+    fn test_approve() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let draw = draw(&mut context, &mut contract, accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(0))
+            .build());
+                
+        let reading = "test reading".to_string();
+        let token = contract.nft_mint(accounts(0), draw.draw_id, reading, None, None, None, None);
+
+        // alice approves bob
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(150000000000000000000)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_approve(token.token_id.clone(), accounts(1), None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        assert!(contract.nft_is_approved(token.token_id.clone(), accounts(1), Some(1)));
+    }
+
+    #[test]
+    fn test_revoke() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let draw = draw(&mut context, &mut contract, accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(0))
+            .build());
+           
+        let reading = "test reading".to_string();
+        let token = contract.nft_mint(accounts(0), draw.draw_id, reading, None, None, None, None);
+
+        // alice approves bob
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(150000000000000000000)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_approve(token.token_id.clone(), accounts(1), None);
+
+        // alice revokes bob
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_revoke(token.token_id.clone(), accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        assert!(!contract.nft_is_approved(token.token_id.clone(), accounts(1), None));
+    }
+
+    #[test]
+    fn test_revoke_all() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        let draw = draw(&mut context, &mut contract, accounts(0));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(0))
+            .build());
+                    
+        let reading = "test reading".to_string();
+        let token = contract.nft_mint(accounts(0), draw.draw_id, reading, None, None, None, None);
+
+        // alice approves bob
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(150000000000000000000)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_approve(token.token_id.clone(), accounts(1), None);
+
+        // alice revokes bob
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_revoke_all(token.token_id.clone());
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
             .is_view(true)
             .attached_deposit(0)
             .build());
         assert!(!contract.nft_is_approved(token.token_id.clone(), accounts(1), Some(1)));
     }
-}
\ No newline at end of file
+
+    const FT_DRAW_PRICE: u128 = 2_000_000;
+    const FT_MINT_PRICE: u128 = 5_000_000;
+
+    fn usdc() -> AccountId {
+        "usdc.near".parse().unwrap()
+    }
+
+    fn accept_usdc(context: &mut VMContextBuilder, contract: &mut Contract) {
+        testing_env!(context.predecessor_account_id(accounts(0)).attached_deposit(0).build());
+        contract.set_ft_price(usdc(), Some(FtPrice { draw_price: U128(FT_DRAW_PRICE), mint_price: U128(FT_MINT_PRICE) }));
+    }
+
+    //`ft_transfer_call` of `amount` usdc from `sender`, returns the unused amount
+    fn ft_pay(context: &mut VMContextBuilder, contract: &mut Contract, sender: AccountId, amount: u128, msg: &str) -> u128 {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(usdc())
+            .build());
+        match contract.ft_on_transfer(sender, U128(amount), msg.to_string()) {
+            PromiseOrValue::Value(unused) => unused.0,
+            PromiseOrValue::Promise(_) => panic!("Expected a value"),
+        }
+    }
+
+    //function calls made to `receiver_id`
+    fn calls_to(receiver_id: &AccountId) -> Vec<String> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| &receipt.receiver_id == receiver_id)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                VmAction::FunctionCall { function_name, .. } => Some(function_name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ft_payment() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        accept_usdc(&mut context, &mut contract);
+        assert_eq!(contract.get_ft_prices()[0].0, usdc());
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min + STORAGE_DEPOSIT);
+
+        // three cards at the per-card price, the rest goes back to the sender
+        let unused = ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE * 3 + 7, r#"{"action":"draw","spread":"three_card"}"#);
+        assert_eq!(unused, 7);
+        assert_eq!(calls_to(&accounts(0)), vec!["reveal_draw".to_string()]);
+        let draw = reveal(&mut context, &mut contract, 0.into());
+        assert_eq!((draw.drawer, draw.spread_id, draw.cards.len()), (accounts(1), "three_card".to_string(), 3));
+        assert_eq!(contract.get_ft_treasury(usdc()).draw_revenue.0, FT_DRAW_PRICE * 3);
+        assert_eq!(contract.get_treasury().draw_revenue.0, 0);
+        assert_eq!(celestial_events("card_drawn")[0]["data"][0]["ft_token_id"], usdc().as_str());
+
+        let msg = r#"{"action":"mint","draw_id":"0","reading":"test reading","receiver_id":"charlie"}"#;
+        assert_eq!(ft_pay(&mut context, &mut contract, accounts(1), FT_MINT_PRICE, msg), 0);
+        assert_eq!(contract.nft_token("0".to_string()).unwrap().owner_id, accounts(2));
+        assert_eq!(contract.get_ft_treasury(usdc()).mint_revenue.0, FT_MINT_PRICE);
+        let events = celestial_events("reading_minted");
+        assert_eq!(events[0]["version"], events::READING_MINTED_VERSION);
+        assert_eq!((&events[0]["data"][0]["price"], &events[0]["data"][0]["ft_token_id"]), (&FT_MINT_PRICE.to_string().into(), &usdc().as_str().into()));
+        assert!(contract.storage_balance_of(accounts(1)).unwrap().available.0 < STORAGE_DEPOSIT);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is not accepted as payment")]
+    fn test_ft_payment_unknown_token() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE, r#"{"action":"draw"}"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transferred amount must be greater than or equal to the draw price: 2000000")]
+    fn test_ft_payment_underpaid() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        accept_usdc(&mut context, &mut contract);
+        ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE - 1, r#"{"action":"draw"}"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "The storage balance of bob cannot cover the draw storage")]
+    fn test_ft_payment_without_storage_balance() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        accept_usdc(&mut context, &mut contract);
+        ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE, r#"{"action":"draw"}"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner or an account with the admin role can call this method")]
+    fn test_set_ft_price_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_ft_price(usdc(), Some(FtPrice { draw_price: U128(1), mint_price: U128(1) }));
+    }
+
+    #[test]
+    fn test_withdraw_ft() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        accept_usdc(&mut context, &mut contract);
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min + STORAGE_DEPOSIT);
+        ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE, r#"{"action":"draw"}"#);
+        reveal(&mut context, &mut contract, 0.into());
+
+        // removing the token keeps its revenue withdrawable
+        accept_usdc(&mut context, &mut contract);
+        contract.set_ft_price(usdc(), None);
+        assert!(contract.get_ft_prices().is_empty());
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+        contract.withdraw_ft(usdc(), None);
+        assert_eq!(calls_to(&usdc()), vec!["ft_transfer".to_string()]);
+        assert_eq!(contract.get_ft_treasury(usdc()).available.0, 0);
+
+        testing_env!(
+            context.attached_deposit(0).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![near_sdk::PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_withdraw_ft(usdc(), contract.get_config().vault_id, U128(FT_DRAW_PRICE)));
+        assert_eq!(contract.get_ft_treasury(usdc()).available.0, FT_DRAW_PRICE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Spread mine has its own price and can only be paid for in NEAR")]
+    fn test_ft_draw_of_priced_spread() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_spread(
+            "mine".to_string(),
+            Spread { name: "Mine".to_string(), positions: vec!["Card".to_string()], price: Some(U128(DRAW_PRICE * 2)) },
+        );
+        accept_usdc(&mut context, &mut contract);
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min + STORAGE_DEPOSIT);
+        ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE, r#"{"action":"draw","spread":"mine"}"#);
+    }
+
+    #[test]
+    fn test_refund_ft_draw() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        accept_usdc(&mut context, &mut contract);
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min + STORAGE_DEPOSIT);
+        ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE, r#"{"action":"draw"}"#);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .block_index(DRAW_REVEAL_TIMEOUT + 1)
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let committed = contract.draws.get(&0).unwrap();
+        let balance = contract.storage_balance_of(accounts(1)).unwrap().total.0;
+        let initial_storage_usage = env::storage_usage();
+        contract.refund_draw(0.into());
+        assert_eq!(calls_to(&usdc()), vec!["ft_transfer".to_string()]);
+        assert!(celestial_events("draw_refunded").is_empty());
+        let released_bytes = initial_storage_usage - env::storage_usage() + committed.reveal_storage_bytes();
+        let released = env::storage_byte_cost() * Balance::from(released_bytes);
+
+        testing_env!(
+            context.attached_deposit(0).predecessor_account_id(accounts(0)).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![near_sdk::PromiseResult::Successful(vec![])],
+        );
+        assert!(contract.resolve_refund_draw(0, committed, released));
+        assert_eq!(refunds_to(&accounts(1)), vec![1]);
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total.0, balance + released);
+        assert_eq!(celestial_events("draw_refunded")[0]["data"][0]["ft_token_id"], usdc().as_str());
+    }
+
+    #[test]
+    fn test_failed_ft_refund_is_restored() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        accept_usdc(&mut context, &mut contract);
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min + STORAGE_DEPOSIT);
+        ft_pay(&mut context, &mut contract, accounts(1), FT_DRAW_PRICE, r#"{"action":"draw"}"#);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .block_index(DRAW_REVEAL_TIMEOUT + 1)
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let committed = contract.draws.get(&0).unwrap();
+        let balance = contract.storage_balance_of(accounts(1)).unwrap().total.0;
+        contract.refund_draw(0.into());
+        assert!(contract.get_draw(0.into()).is_none());
+
+        testing_env!(
+            context.attached_deposit(0).predecessor_account_id(accounts(0)).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![near_sdk::PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_refund_draw(0, committed, 1));
+        assert!(contract.get_draw(0.into()).is_some());
+        assert_eq!(refunds_to(&accounts(1)), vec![1]);
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total.0, balance);
+        assert!(celestial_events("draw_refunded").is_empty());
+
+        // the drawer can try again
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.refund_draw(0.into());
+        assert_eq!(calls_to(&usdc()), vec!["ft_transfer".to_string()]);
+    }
+
+    #[test]
+    fn test_burn() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint_with_question(&mut context, &mut contract, sealed_question("test question", b"salt"));
+        let kept = mint(&mut context, &mut contract, accounts(1));
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT).build());
+        contract.nft_approve(token.token_id.clone(), accounts(2), None);
+        assert_eq!(contract.nft_total_supply().0, 2);
+
+        let initial_storage_usage = env::storage_usage();
+        testing_env!(context.storage_usage(initial_storage_usage).attached_deposit(1).build());
+        contract.nft_burn(token.token_id.clone());
+        let released = env::storage_byte_cost() * Balance::from(initial_storage_usage - env::storage_usage());
+        assert!(released > 0);
+        assert_eq!(refunds_to(&accounts(1)), vec![released + 1]);
+        assert!(get_logs().iter().any(|log| log.starts_with(r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_burn""#)));
+
+        assert_eq!(contract.nft_token(token.token_id.clone()), None);
+        assert_eq!(contract.nft_total_supply().0, 1);
+        let owned: Vec<TokenId> = contract.nft_tokens_for_owner(accounts(1), None, None).into_iter().map(|t| t.token_id).collect();
+        assert_eq!(owned, vec![kept.token_id]);
+        assert!(contract.tokens.approvals_by_id.as_ref().unwrap().get(&token.token_id).is_none());
+        assert_eq!(contract.get_question(token.token_id.clone()), None);
+        assert!(contract.royalties.get(&token.token_id).is_none());
+        assert_eq!(contract.get_num(), "2");
+    }
+
+    #[test]
+    fn test_burn_credits_storage_balance() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let min = contract.storage_balance_bounds().min.0;
+        storage_deposit(&mut context, &mut contract, accounts(1), min);
+        let token = mint(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+        contract.nft_burn(token.token_id);
+        assert!(contract.storage_balance_of(accounts(1)).unwrap().available.0 > 0);
+        assert_eq!(refunds_to(&accounts(1)), vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner can burn it")]
+    fn test_burn_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(2)).build());
+        contract.nft_burn(token.token_id);
+    }
+
+    fn mint_soulbound(context: &mut VMContextBuilder, contract: &mut Contract, owner: AccountId) -> Token {
+        let draw = draw(context, contract, owner.clone());
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(owner.clone())
+            .build());
+        contract.nft_mint(owner, draw.draw_id, "test reading".to_string(), None, Some(true), None, None)
+    }
+
+    #[test]
+    fn test_soulbound() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint_soulbound(&mut context, &mut contract, accounts(1));
+        let layout: near_sdk::serde_json::Value = near_sdk::serde_json::from_str(&token.metadata.unwrap().extra.unwrap()).unwrap();
+        assert_eq!(layout["soulbound"], true);
+
+        // the owner can still burn it
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+        contract.nft_burn(token.token_id.clone());
+        assert_eq!(contract.nft_token(token.token_id.clone()), None);
+        assert!(!contract.soulbound.contains(&token.token_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Soulbound tokens cannot be transferred")]
+    fn test_soulbound_transfer() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint_soulbound(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_transfer(accounts(2), token.token_id, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Soulbound tokens cannot be transferred")]
+    fn test_soulbound_transfer_payout() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint_soulbound(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_transfer_payout(accounts(2), token.token_id, None, None, U128(1_000), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Soulbound tokens cannot be approved")]
+    fn test_soulbound_approve() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint_soulbound(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT).build());
+        contract.nft_approve(token.token_id, accounts(2), None);
+    }
+
+    fn interpreter_key() -> (ed25519_dalek::ExpandedSecretKey, ed25519_dalek::PublicKey) {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[7; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        (ed25519_dalek::ExpandedSecretKey::from(&secret), public)
+    }
+
+    fn register_interpreter(context: &mut VMContextBuilder, contract: &mut Contract) {
+        let (_, public) = interpreter_key();
+        let public_key = PublicKey::try_from([vec![0], public.to_bytes().to_vec()].concat()).unwrap();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_interpreter(accounts(4), Some(public_key));
+    }
+
+    fn sign_reading(contract: &Contract, draw_id: U64, reading: &str) -> ReadingSignature {
+        let (secret, public) = interpreter_key();
+        let message = contract.get_reading_message(draw_id, reading.to_string());
+        ReadingSignature { interpreter_id: accounts(4), signature: secret.sign(&message.0, &public).to_bytes().to_vec().into() }
+    }
+
+    #[test]
+    fn test_signed_reading() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        register_interpreter(&mut context, &mut contract);
+        assert_eq!(contract.get_interpreters().len(), 1);
+
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        let signature = sign_reading(&contract, draw.draw_id, "test reading");
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let token = contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, Some(signature), None);
+        let layout: near_sdk::serde_json::Value = near_sdk::serde_json::from_str(&token.metadata.unwrap().extra.unwrap()).unwrap();
+        assert_eq!(layout["verified_by"], accounts(4).to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid reading signature")]
+    fn test_signed_reading_altered() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        register_interpreter(&mut context, &mut contract);
+
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        let signature = sign_reading(&contract, draw.draw_id, "test reading");
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "another reading".to_string(), None, None, Some(signature), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Interpreter not found")]
+    fn test_signed_reading_removed_interpreter() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        register_interpreter(&mut context, &mut contract);
+
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        let signature = sign_reading(&contract, draw.draw_id, "test reading");
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_interpreter(accounts(4), None);
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, Some(signature), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner or an account with the admin role can call this method")]
+    fn test_set_interpreter_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_interpreter(accounts(4), None);
+    }
+
+    #[test]
+    fn test_mint_layout() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        testing_env!(context.block_timestamp(1_700_000_000_000_000_000).build());
+        let token = mint_with_question(&mut context, &mut contract, sealed_question("Will it rain?", b"salt"));
+        let metadata = token.metadata.unwrap();
+        assert_eq!(metadata.issued_at, Some("1700000000000".to_string()));
+        let layout: near_sdk::serde_json::Value = near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(layout["cards"][0]["spread_position"], "Card");
+        assert_eq!(layout["cards"][0]["arcana"], "major");
+        assert_eq!(layout["question_hash"], near_sdk::serde_json::to_value(sealed_question("Will it rain?", b"salt").hash).unwrap());
+        assert_eq!(layout["block_height"], "0");
+        assert_eq!(layout["revealed_at"], "1");
+    }
+
+    #[test]
+    fn test_mint_reference() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        let reference = ReadingReference { reference: "ipfs://reading.json".to_string(), reference_hash: env::sha256(b"{}").into() };
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let token = contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, Some(reference.clone()));
+        let metadata = token.metadata.unwrap();
+        assert_eq!(metadata.reference, Some(reference.reference));
+        assert_eq!(metadata.reference_hash, Some(reference.reference_hash));
+    }
+
+    #[test]
+    #[should_panic(expected = "Reference hash must be a 32-byte sha256 hash")]
+    fn test_mint_invalid_reference_hash() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        let reference = ReadingReference { reference: "ipfs://reading.json".to_string(), reference_hash: vec![0; 8].into() };
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint(accounts(1), draw.draw_id, "test reading".to_string(), None, None, None, Some(reference));
+    }
+
+    #[test]
+    fn test_orientation_json() {
+        let parse = |json: &str| near_sdk::serde_json::from_str::<Orientation>(json);
+        assert_eq!(parse(r#""upright""#).unwrap(), Orientation::Upright);
+        assert_eq!(parse(r#""reversed""#).unwrap(), Orientation::Reversed);
+        assert_eq!(parse(r#""reverse""#).unwrap(), Orientation::Reversed);
+        assert!(parse(r#""sideways""#).is_err());
+        assert_eq!(near_sdk::serde_json::to_string(&Orientation::Reversed).unwrap(), r#""reversed""#);
+    }
+
+    #[test]
+    fn test_migrate_reading_titles() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let titles = [
+            "Reading#0: 0 The Fool in reverse",
+            "Reading#1: I The Magician in upright",
+            "Reading#2: II The High Priestess in sideways",
+            "Reading#3: XIII Death in sideways in upright",
+            "Reading#4: Not A Card in reverse",
+        ];
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT).build());
+        for (token_id, title) in titles.iter().enumerate() {
+            // the first deployment minted without `extra`
+            let metadata = TokenMetadata { title: Some(title.to_string()), extra: None, ..sample_token_metadata() };
+            contract.tokens.internal_mint_with_refund(token_id.to_string(), accounts(1), Some(metadata), None);
+            contract.minted += 1;
+        }
+
+        let next = contract.migrate_reading_titles(U64(0), 2);
+        assert_eq!(next, U64(2));
+        let next = contract.migrate_reading_titles(next, 3);
+        assert_eq!(next, U64(5));
+        let title = |contract: &Contract, token_id: &str| contract.nft_token(token_id.to_string()).unwrap().metadata.unwrap().title.unwrap();
+        assert_eq!(title(&contract, "0"), "Reading#0: 0 The Fool in reversed");
+        assert_eq!(title(&contract, "1"), "Reading#1: I The Magician in upright");
+        assert_eq!(title(&contract, "2"), "Reading#2: II The High Priestess");
+        //only the card is kept from a title with an injected orientation
+        assert_eq!(title(&contract, "3"), "Reading#3: XIII Death");
+        assert_eq!(title(&contract, "4"), "Reading#4: Not A Card in reverse");
+    }
+
+    #[test]
+    #[should_panic(expected = "Reading must be at most 4096 bytes, got 4098")]
+    fn test_mint_reading_too_long() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let draw = draw(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        // 2049 characters, but 2 bytes each
+        contract.nft_mint(accounts(1), draw.draw_id, "é".repeat(2049), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Titles of readings of spread Single Card from deck Celestial can be")]
+    fn test_set_config_title_too_short() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_config(Config { max_title_len: 16, ..Config::default() });
+    }
+
+    #[test]
+    #[should_panic(expected = "more than the max title length of 128")]
+    fn test_set_spread_title_too_long() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let name = "Long".repeat(30);
+        contract.set_spread("long".to_string(), Spread { name, positions: vec!["A".to_string(), "B".to_string()], price: None });
+    }
+
+    #[test]
+    #[should_panic(expected = "more than the max title length of 128")]
+    fn test_set_deck_title_too_long() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let mut deck = sample_deck();
+        deck.cards[0].name = "Long".repeat(30);
+        contract.set_deck("long".to_string(), deck);
+    }
+
+    #[test]
+    #[should_panic(expected = "Question must be at most 1024 bytes, got 1025")]
+    fn test_reveal_question_too_long() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let question = "?".repeat(1025);
+        let token = mint_with_question(&mut context, &mut contract, sealed_question(&question, b"salt"));
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT).build());
+        contract.reveal_question(token.token_id, question, b"salt".to_vec().into());
+    }
+
+    #[test]
+    fn test_mint_storage_grows_with_reading() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        // the first reading of an owner also stores their token set
+        mint(&mut context, &mut contract, accounts(1));
+        let mut storage_costs = Vec::new();
+        for reading in ["short".to_string(), "long".repeat(250)] {
+            let draw = draw(&mut context, &mut contract, accounts(1));
+            testing_env!(context
+                .storage_usage(env::storage_usage())
+                .attached_deposit(STORAGE_DEPOSIT + MINT_PRICE)
+                .predecessor_account_id(accounts(1))
+                .build());
+            contract.nft_mint(accounts(1), draw.draw_id, reading, None, None, None, None);
+            storage_costs.push(STORAGE_DEPOSIT - refunds_to(&accounts(1))[0]);
+        }
+        assert!(storage_costs[1] - storage_costs[0] >= env::storage_byte_cost() * (1000 - 5));
+    }
+
+    fn annotate(context: &mut VMContextBuilder, contract: &mut Contract, token_id: &TokenId, text: &str) -> U64 {
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(STORAGE_DEPOSIT).build());
+        contract.annotate(token_id.clone(), text.to_string())
+    }
+
+    #[test]
+    fn test_annotate() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint(&mut context, &mut contract, accounts(1));
+
+        context.block_timestamp(1_700_000_000_000_000_000);
+        assert_eq!(annotate(&mut context, &mut contract, &token.token_id, "It came true"), U64(0));
+        assert_eq!(celestial_events("reading_annotated")[0]["data"][0]["index"], "0");
+        context.block_timestamp(1_700_000_060_000_000_000);
+        assert_eq!(annotate(&mut context, &mut contract, &token.token_id, "Read again a month later"), U64(1));
+        assert_eq!(contract.get_annotation_count(token.token_id.clone()), U64(2));
+
+        let annotations = contract.get_annotations(token.token_id.clone(), Some(U128(1)), Some(10));
+        assert_eq!(annotations, vec![Annotation { text: "Read again a month later".to_string(), created_at: U64(1_700_000_060_000) }]);
+        let metadata = contract.nft_token(token.token_id.clone()).unwrap().metadata.unwrap();
+        assert_eq!(metadata.updated_at, Some("1700000060000".to_string()));
+
+        // burning the reading drops its annotations
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+        contract.nft_burn(token.token_id.clone());
+        assert_eq!(contract.get_annotation_count(token.token_id), U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner can annotate it")]
+    fn test_annotate_by_stranger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint(&mut context, &mut contract, accounts(1));
+        context.predecessor_account_id(accounts(2));
+        annotate(&mut context, &mut contract, &token.token_id, "Not mine");
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal to the annotation price + storage")]
+    fn test_annotate_without_deposit() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let token = mint(&mut context, &mut contract, accounts(1));
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(0).build());
+        contract.annotate(token.token_id, "It came true".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner can change the vault")]
+    fn test_set_vault_by_admin() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+
+        // an admin can change prices, but not where the revenue goes
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_config(Config { draw_price: U128(DRAW_PRICE * 2), ..Config::default() });
+        contract.set_config(Config { vault_id: accounts(1), ..contract.get_config() });
+    }
+}
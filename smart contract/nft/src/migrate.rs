@@ -0,0 +1,179 @@
+/*!
+Versioned contract state and upgrades.
+NOTES:
+  - The layout of the stored state is recorded as a `StateVersion` under its own storage key,
+    written by `new` and `migrate`. Deployments from before versioning have no version and are `V0`.
+  - Only released layouts get a version. `V0` is the first deployment and `V1` is the release
+    that introduced versioning, so everything added before it ships (draws, roles, the owner
+    transfer, pause flags, FT payments, soulbound readings, interpreters and annotations) is part
+    of `V1` and is converted from `V0` directly.
+  - When a field of a released `Contract` layout changes, keep a copy of that layout here as
+    `ContractV<n>`, add a `StateVersion` variant for the new layout, bump `CURRENT_STATE_VERSION`
+    and convert the old state in `migrate`.
+  - `upgrade` deploys new code and calls `migrate` in the same batch, so a failing migration
+    reverts the deployment as well.
+  - Token metadata is too large to rewrite in one call, so it is migrated in pages with
+    `migrate_reading_titles` after the upgrade.
+*/
+
+use near_contract_standards::non_fungible_token::metadata::NFTContractMetadata;
+use near_contract_standards::non_fungible_token::NonFungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, UnorderedMap};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, Gas};
+
+use crate::deck::{Deck, DEFAULT_DECK, MAJOR_ARCANA_NAME};
+use crate::roles::Role;
+use crate::spread::default_spreads;
+use crate::{Config, Contract, ContractExt, Orientation, PauseState, StorageKey, Treasury};
+
+/// Gas kept by `upgrade` itself, the rest of the prepaid gas goes to `migrate`.
+const GAS_FOR_UPGRADE: Gas = Gas(10_000_000_000_000);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+    /// `ContractV0`, the first deployment.
+    V0,
+    /// The current `Contract` layout, the first release with a recorded version.
+    V1,
+}
+
+pub const CURRENT_STATE_VERSION: StateVersion = StateVersion::V1;
+
+/// State layout of the first deployment, before draws were stored on chain
+/// and while the token counter was still an `i8`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV0 {
+    pub tokens: NonFungibleToken,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub minted: i8,
+}
+
+//stored state version, `V0` if none was recorded
+fn state_version() -> StateVersion {
+    LazyOption::new(StorageKey::StateVersion, None).get().unwrap_or(StateVersion::V0)
+}
+
+pub(crate) fn write_state_version() {
+    LazyOption::new(StorageKey::StateVersion, None).set(&CURRENT_STATE_VERSION);
+}
+
+//"Reading#{id}: {card} in {orientation}" with the orientation renamed, or dropped with anything
+//else after the card if it is not one, `None` if the title is not a reading of a Major Arcana card
+//as the first deployment minted them, or needs no change
+fn migrated_title(title: &str) -> Option<String> {
+    let (token_id, rest) = title.strip_prefix("Reading#")?.split_once(": ")?;
+    token_id.parse::<u64>().ok()?;
+    let card = MAJOR_ARCANA_NAME.iter().find(|card| rest.strip_prefix(**card).is_some_and(|rest| rest.starts_with(" in ")))?;
+    let migrated = match rest[card.len() + " in ".len()..].parse::<Orientation>() {
+        Ok(orientation) => format!("Reading#{}: {} in {}", token_id, card, orientation.name()),
+        Err(_) => format!("Reading#{}: {}", token_id, card),
+    };
+    Some(migrated).filter(|migrated| migrated != title)
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Reads the stored state in whichever layout it has and rewrites it in the current one.
+    /// Only the contract owner, or the contract itself during `upgrade`, can call this.
+    /// Existing tokens keep their ids, and the counter continues from the old value.
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let this = match state_version() {
+            StateVersion::V0 => Self::from_v0(env::state_read().expect("Failed to read old state")),
+            StateVersion::V1 => env::state_read().expect("Failed to read old state"),
+        };
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == env::current_account_id() || caller == this.tokens.owner_id,
+            "Only the contract owner can call this method"
+        );
+        write_state_version();
+        this
+    }
+
+    /// Deploys the wasm passed as the raw call input and migrates the state in the same batch.
+    /// Only the contract owner can call this.
+    pub fn upgrade(&self) {
+        self.assert_owner();
+        let code = env::input().expect("Missing contract code");
+        assert!(!code.is_empty(), "Missing contract code");
+        let promise = env::promise_batch_create(&env::current_account_id());
+        //the deploy fee grows with the code size and is charged as the action is added, so
+        //`migrate` gets the gas left after it
+        env::promise_batch_action_deploy_contract(promise, &code);
+        env::promise_batch_action_function_call(
+            promise,
+            "migrate",
+            &[],
+            0,
+            env::prepaid_gas() - env::used_gas() - GAS_FOR_UPGRADE,
+        );
+        env::promise_return(promise);
+    }
+
+    /// Migrates the titles of the readings with ids `from_token_id` to `from_token_id + limit - 1`
+    /// minted by the first deployment to the current orientation names: "reverse" becomes "reversed",
+    /// and a title orientation that is not "upright" or "reversed", which the first deployment
+    /// minted as given, is removed. Returns the id to continue from, `get_num` once every reading is done.
+    /// Only the owner or an admin can call this. Storage growth is paid from the attached deposit.
+    #[payable]
+    pub fn migrate_reading_titles(&mut self, from_token_id: U64, limit: u64) -> U64 {
+        self.assert_role(Role::Admin);
+        let initial_storage_usage = env::storage_usage();
+        let end = self.minted.min(from_token_id.0.saturating_add(limit)).max(from_token_id.0);
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            for token_id in from_token_id.0..end {
+                let token_id = token_id.to_string();
+                //readings of the first deployment are the ones minted without `extra`
+                if let Some(mut metadata) = token_metadata_by_id.get(&token_id).filter(|metadata| metadata.extra.is_none()) {
+                    if let Some(title) = metadata.title.as_deref().and_then(migrated_title) {
+                        metadata.title = Some(title);
+                        token_metadata_by_id.insert(&token_id, &metadata);
+                    }
+                }
+            }
+        }
+        self.charge_and_refund(0, initial_storage_usage, "migration");
+        end.into()
+    }
+
+    //view the layout version of the contract state
+    pub fn get_state_version(&self) -> StateVersion {
+        state_version()
+    }
+
+    fn from_v0(old: ContractV0) -> Self {
+        assert!(old.minted >= 0, "Old token counter is negative");
+        let mut this = Self {
+            tokens: old.tokens,
+            metadata: old.metadata,
+            minted: old.minted as u64,
+            draws: LookupMap::new(StorageKey::Draws),
+            next_draw_id: 0,
+            treasury: Treasury::default(),
+            config: Config::default(),
+            spreads: UnorderedMap::new(StorageKey::Spreads),
+            decks: UnorderedMap::new(StorageKey::Decks),
+            questions: LookupMap::new(StorageKey::Questions),
+            royalties: LookupMap::new(StorageKey::Royalties),
+            soulbound: LookupSet::new(StorageKey::Soulbound),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            ft_prices: UnorderedMap::new(StorageKey::FtPrices),
+            ft_treasuries: LookupMap::new(StorageKey::FtTreasuries),
+            roles: UnorderedMap::new(StorageKey::Roles),
+            pending_owner_id: None,
+            paused: PauseState::default(),
+            interpreters: UnorderedMap::new(StorageKey::Interpreters),
+            annotations: LookupMap::new(StorageKey::Annotations),
+        };
+        this.decks.insert(&DEFAULT_DECK.to_string(), &Deck::celestial());
+        for (spread_id, spread) in default_spreads() {
+            this.spreads.insert(&spread_id, &spread);
+        }
+        this
+    }
+}
@@ -0,0 +1,56 @@
+use near_contract_standards::non_fungible_token::Token;
+use near_sdk::json_types::U64;
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::events::ReadingMinted;
+use crate::{Contract, ContractExt, ReadingReference, ReadingSignature, SealedQuestion};
+
+#[near_bindgen]
+impl Contract {
+    /// Mints the pending draw `draw_id` as a reading NFT for `receiver_id`.
+    /// Only the account that made the draw, or a minter, can mint it, and only once.
+    /// The optional `question` is stored sealed with the token, see `reveal_question`.
+    /// A `soulbound` reading stays with `receiver_id`: it cannot be transferred or approved, only burned.
+    /// A reading with the `signature` of a registered interpreter is marked verified, see `set_interpreter`.
+    /// The optional `reference` points at off-chain JSON about the reading, with its sha256 hash.
+    #[payable]
+    pub fn nft_mint(
+        &mut self,
+        receiver_id: AccountId,
+        draw_id: U64,
+        reading: String,
+        question: Option<SealedQuestion>,
+        soulbound: Option<bool>,
+        signature: Option<ReadingSignature>,
+        reference: Option<ReadingReference>,
+    ) -> Token {
+        self.paused.assert_mints_open();
+        let deposit = env::attached_deposit();
+        assert!(deposit > 0, "Attached deposit must be greater than 0");
+        //make sure the deposit is greater than the price
+        let mint_price = self.config.mint_price.0;
+        assert!(deposit >= mint_price, "Attached deposit must be greater than or equal to the mint price: {:?}", mint_price);
+        let initial_storage_usage = env::storage_usage();
+        let token = self.internal_mint(
+            &env::predecessor_account_id(),
+            receiver_id,
+            draw_id,
+            reading,
+            question,
+            soulbound.unwrap_or(false),
+            signature,
+            reference,
+        );
+        self.charge_and_refund(mint_price, initial_storage_usage, "mint");
+        self.treasury.record_mint(mint_price);
+        ReadingMinted {
+            owner_id: &token.owner_id,
+            token_id: &token.token_id,
+            draw_id,
+            price: mint_price.into(),
+            ft_token_id: None,
+        }
+        .emit();
+        token
+    }
+}
@@ -0,0 +1,112 @@
+/*!
+Readings signed by a registered interpreter.
+NOTES:
+  - The owner or an admin registers the ed25519 key of each interpreter with `set_interpreter`.
+    The key is kept apart from the interpreter account's access keys, it only signs readings.
+  - An interpreter signs the borsh serialization of `ReadingMessage` for a revealed draw: the
+    contract id, the draw id, each card with its spread position and orientation, and the
+    `sha256` of the reading. `get_reading_message` returns these bytes for a pending draw.
+  - A reading minted with a valid `ReadingSignature` records the interpreter as `verified_by`
+    in the token's `extra`. Readings minted without a signature are still accepted, unverified.
+*/
+
+use near_sdk::borsh::{self, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, CurveType, PublicKey};
+use std::convert::TryFrom;
+
+use crate::roles::Role;
+use crate::{Contract, ContractExt, Draw, Orientation};
+
+/// Signature of an interpreter over the `ReadingMessage` of the minted draw, passed to `nft_mint`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReadingSignature {
+    pub interpreter_id: AccountId,
+    pub signature: Base64VecU8,
+}
+
+/// What an interpreter signs, see the module notes.
+#[derive(BorshSerialize)]
+pub struct ReadingMessage {
+    pub contract_id: AccountId,
+    pub draw_id: u64,
+    pub cards: Vec<SignedCard>,
+    pub reading_hash: Vec<u8>,
+}
+
+/// A card of the signed draw, at the spread position `position`.
+#[derive(BorshSerialize)]
+pub struct SignedCard {
+    pub card_index: u8,
+    pub position: String,
+    pub orientation: Orientation,
+}
+
+impl ReadingMessage {
+    pub fn new(draw_id: u64, draw: &Draw, reading: &str) -> Self {
+        Self {
+            contract_id: env::current_account_id(),
+            draw_id,
+            cards: draw
+                .positions
+                .iter()
+                .zip(&draw.cards)
+                .map(|(position, card)| SignedCard {
+                    card_index: card.card_index,
+                    position: position.clone(),
+                    orientation: card.orientation,
+                })
+                .collect(),
+            reading_hash: env::sha256(reading.as_bytes()),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Registers `public_key` as the ed25519 key of the interpreter `interpreter_id`, replacing its
+    /// previous key, or removes the interpreter if omitted. Readings it signed before stay verified.
+    /// Only the owner or an admin can call this.
+    pub fn set_interpreter(&mut self, interpreter_id: AccountId, public_key: Option<PublicKey>) {
+        self.assert_role(Role::Admin);
+        match public_key {
+            Some(public_key) => {
+                assert!(public_key.curve_type() == CurveType::ED25519, "Interpreter key must be an ed25519 key");
+                self.interpreters.insert(&interpreter_id, &public_key);
+            }
+            None => {
+                self.interpreters.remove(&interpreter_id);
+            }
+        }
+    }
+
+    //view registered interpreters and their keys
+    pub fn get_interpreters(&self) -> Vec<(AccountId, PublicKey)> {
+        self.interpreters.to_vec()
+    }
+
+    //view the bytes an interpreter signs for `reading` of the revealed draw `draw_id`
+    pub fn get_reading_message(&self, draw_id: U64, reading: String) -> Base64VecU8 {
+        let draw = self.draws.get(&draw_id.0).expect("Draw not found");
+        assert!(draw.is_revealed(), "Draw is not revealed yet");
+        ReadingMessage::new(draw_id.0, &draw, &reading).try_to_vec().unwrap().into()
+    }
+
+    ///////////////////////////////////////////////////////
+    // HELPER FUNCTIONS                                  //
+    //////////////////////////////////////////////////////
+
+    //checks `signature` is a registered interpreter's signature over `reading` of `draw`
+    pub(crate) fn assert_reading_signature(&self, signature: &ReadingSignature, draw_id: u64, draw: &Draw, reading: &str) {
+        let public_key = self.interpreters.get(&signature.interpreter_id).expect("Interpreter not found");
+        let public_key =
+            ed25519_dalek::PublicKey::from_bytes(&public_key.as_bytes()[1..]).expect("Invalid interpreter key");
+        let message = ReadingMessage::new(draw_id, draw, reading).try_to_vec().unwrap();
+        let valid = ed25519_dalek::Signature::try_from(&signature.signature.0[..])
+            .map(|sig| public_key.verify_strict(&message, &sig).is_ok())
+            .unwrap_or(false);
+        assert!(valid, "Invalid reading signature");
+    }
+}
@@ -0,0 +1,46 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::events::PauseUpdated;
+use crate::roles::Role;
+use crate::{Contract, ContractExt};
+
+/// Emergency stops, one per kind of action. Pending draws and minted readings
+/// are kept while paused and can be used again once the flag is cleared.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    pub draws: bool,
+    pub mints: bool,
+    pub transfers: bool,
+}
+
+impl PauseState {
+    pub fn assert_draws_open(&self) {
+        assert!(!self.draws, "Draws are paused");
+    }
+
+    pub fn assert_mints_open(&self) {
+        assert!(!self.mints, "Mints are paused");
+    }
+
+    pub fn assert_transfers_open(&self) {
+        assert!(!self.transfers, "Transfers are paused");
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Replaces the pause flags. Only the owner or a pauser can call this.
+    pub fn set_paused(&mut self, paused: PauseState) {
+        self.assert_role(Role::Pauser);
+        PauseUpdated { updated_by: &env::predecessor_account_id(), paused: &paused }.emit();
+        self.paused = paused;
+    }
+
+    //view which actions are paused
+    pub fn get_paused(&self) -> PauseState {
+        self.paused
+    }
+}
@@ -0,0 +1,151 @@
+/*!
+Payments in NEP-141 fungible tokens.
+NOTES:
+  - A user pays by calling `ft_transfer_call` on an accepted token with this contract as the
+    receiver and a JSON `msg`, either `{"action":"draw","spread":"three_card","deck":"celestial"}`
+    or `{"action":"mint","draw_id":"3","reading":"...","receiver_id":"...","question":{...},
+    "soulbound":true,"signature":{...},"reference":{...}}`.
+    Omitted fields default like the arguments of `draw_cards` and `nft_mint`.
+  - Accepted tokens and their prices are set with `set_ft_price`. A draw costs the token's
+    `draw_price` per card. A spread with its own `price` is priced in NEAR only, so it cannot
+    be drawn with a token.
+  - The part of the transferred amount above the price is returned to the sender by the token
+    contract, and so is the whole amount if the draw or mint fails.
+  - No NEAR comes with `ft_on_transfer`, so the storage of a draw or reading is paid from the
+    sender's storage balance, see `storage_deposit`.
+*/
+
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance, PromiseOrValue};
+
+use crate::deck::DEFAULT_DECK;
+use crate::events::ReadingMinted;
+use crate::roles::Role;
+use crate::spread::SINGLE_SPREAD;
+use crate::{Contract, ContractExt, ReadingReference, ReadingSignature, SealedQuestion};
+
+/// Prices in the smallest unit of an accepted fungible token.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtPrice {
+    pub draw_price: U128,
+    pub mint_price: U128,
+}
+
+impl FtPrice {
+    pub fn assert_valid(&self) {
+        assert!(self.draw_price.0 > 0, "Draw price must be greater than 0");
+        assert!(self.mint_price.0 > 0, "Mint price must be greater than 0");
+    }
+}
+
+/// The `msg` of a payment, see the module notes.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum FtPaymentMsg {
+    Draw {
+        #[serde(alias = "spread")]
+        spread_id: Option<String>,
+        #[serde(alias = "deck")]
+        deck_id: Option<String>,
+    },
+    Mint {
+        draw_id: U64,
+        receiver_id: Option<AccountId>,
+        reading: String,
+        question: Option<SealedQuestion>,
+        soulbound: Option<bool>,
+        signature: Option<ReadingSignature>,
+        reference: Option<ReadingReference>,
+    },
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Pays for the draw or mint described by `msg` with `amount` of the calling token on behalf of
+    /// `sender_id`, and returns the part of `amount` that was not used.
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let ft_token_id = env::predecessor_account_id();
+        let ft_price = self.ft_prices.get(&ft_token_id).expect("Token is not accepted as payment");
+        let msg: FtPaymentMsg = near_sdk::serde_json::from_str(&msg).expect("Invalid payment message");
+        let used = match msg {
+            FtPaymentMsg::Draw { spread_id, deck_id } => {
+                self.paused.assert_draws_open();
+                let spread_id = spread_id.unwrap_or_else(|| SINGLE_SPREAD.to_string());
+                let spread = self.spreads.get(&spread_id).expect("Spread not found");
+                assert!(spread.price.is_none(), "Spread {} has its own price and can only be paid for in NEAR", spread_id);
+                let deck_id = deck_id.unwrap_or_else(|| DEFAULT_DECK.to_string());
+                let deck = self.internal_deck(&deck_id);
+                self.config.assert_title_fits(&spread, &deck);
+                let draw_price = ft_price.draw_price.0 * spread.positions.len() as Balance;
+                assert!(amount.0 >= draw_price, "Transferred amount must be greater than or equal to the draw price: {:?}", draw_price);
+                let (_, storage_cost) =
+                    self.internal_commit_draw(sender_id.clone(), spread_id, &spread, deck_id, draw_price, Some(ft_token_id));
+                self.internal_storage_pay_all(&sender_id, storage_cost, "draw");
+                draw_price
+            }
+            FtPaymentMsg::Mint { draw_id, receiver_id, reading, question, soulbound, signature, reference } => {
+                self.paused.assert_mints_open();
+                let mint_price = ft_price.mint_price.0;
+                assert!(amount.0 >= mint_price, "Transferred amount must be greater than or equal to the mint price: {:?}", mint_price);
+                let initial_storage_usage = env::storage_usage();
+                let receiver_id = receiver_id.unwrap_or_else(|| sender_id.clone());
+                let token = self.internal_mint(
+                    &sender_id,
+                    receiver_id,
+                    draw_id,
+                    reading,
+                    question,
+                    soulbound.unwrap_or(false),
+                    signature,
+                    reference,
+                );
+                let storage_usage = env::storage_usage();
+                if storage_usage < initial_storage_usage {
+                    let released = env::storage_byte_cost() * Balance::from(initial_storage_usage - storage_usage);
+                    self.internal_storage_credit(&sender_id, released);
+                }
+                let storage_cost = env::storage_byte_cost() * Balance::from(storage_usage.saturating_sub(initial_storage_usage));
+                self.internal_storage_pay_all(&sender_id, storage_cost, "mint");
+                self.update_ft_treasury(&ft_token_id, |treasury| treasury.record_mint(mint_price));
+                ReadingMinted {
+                    owner_id: &token.owner_id,
+                    token_id: &token.token_id,
+                    draw_id,
+                    price: mint_price.into(),
+                    ft_token_id: Some(&ft_token_id),
+                }
+                .emit();
+                mint_price
+            }
+        };
+        PromiseOrValue::Value(U128(amount.0 - used))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Accepts the fungible token `ft_token_id` as payment at `price`, or stops accepting it if omitted.
+    /// Only the owner or an admin can call this. Revenue already accrued in the token stays withdrawable.
+    pub fn set_ft_price(&mut self, ft_token_id: AccountId, price: Option<FtPrice>) {
+        self.assert_role(Role::Admin);
+        match price {
+            Some(price) => {
+                price.assert_valid();
+                self.ft_prices.insert(&ft_token_id, &price);
+            }
+            None => {
+                self.ft_prices.remove(&ft_token_id);
+            }
+        }
+    }
+
+    //view accepted fungible tokens and their prices
+    pub fn get_ft_prices(&self) -> Vec<(AccountId, FtPrice)> {
+        self.ft_prices.to_vec()
+    }
+}
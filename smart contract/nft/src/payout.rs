@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use near_contract_standards::non_fungible_token::{refund_approved_account_ids, TokenId};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Balance};
+
+use crate::deck::Deck;
+use crate::{Contract, ContractExt};
+
+/// Basis points of a sale, 10_000 is the whole balance.
+pub const ROYALTY_DENOMINATOR: u32 = 10_000;
+/// Cap on the sum of royalties of a token, so a sale always leaves half to the seller.
+pub const MAX_TOTAL_ROYALTY: u32 = 5_000;
+
+/// NEP-199 payout: the amount of a sale each account receives.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    pub payout: HashMap<AccountId, U128>,
+}
+
+pub(crate) fn assert_total_royalty(total: u32) {
+    assert!(total <= MAX_TOTAL_ROYALTY, "Total royalty must be at most {} basis points", MAX_TOTAL_ROYALTY);
+}
+
+#[near_bindgen]
+impl Contract {
+    //view how a sale of `token_id` for `balance` is split
+    pub fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: Option<u32>) -> Payout {
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        self.internal_payout(&token_id, owner_id, balance.0, max_len_payout)
+    }
+
+    /// Transfers `token_id` like `nft_transfer` and returns how the marketplace
+    /// must split the sale `balance` between the royalty accounts and the previous owner.
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        assert_one_yocto();
+        self.paused.assert_transfers_open();
+        self.assert_transferable(&token_id);
+        let sender_id = env::predecessor_account_id();
+        let (previous_owner_id, approved_account_ids) =
+            self.tokens.internal_transfer(&sender_id, &receiver_id, &token_id, approval_id, memo);
+        if let Some(approved_account_ids) = approved_account_ids {
+            refund_approved_account_ids(previous_owner_id.clone(), &approved_account_ids);
+        }
+        self.internal_payout(&token_id, previous_owner_id, balance.0, max_len_payout)
+    }
+
+    //royalties of a reading drawn from `deck`: the vault and the deck artist, if any
+    pub(crate) fn royalties_for(&self, deck: &Deck) -> HashMap<AccountId, u32> {
+        let mut royalties = HashMap::new();
+        if self.config.vault_royalty > 0 {
+            royalties.insert(self.config.vault_id.clone(), self.config.vault_royalty);
+        }
+        if let Some(artist_id) = &deck.artist_id {
+            if deck.artist_royalty > 0 {
+                *royalties.entry(artist_id.clone()).or_insert(0) += deck.artist_royalty;
+            }
+        }
+        royalties
+    }
+
+    //splits `balance` between the royalty accounts of `token_id` and `owner_id`, who gets the rest
+    fn internal_payout(&self, token_id: &TokenId, owner_id: AccountId, balance: Balance, max_len_payout: Option<u32>) -> Payout {
+        let royalties = self.royalties.get(token_id).unwrap_or_default();
+        let recipients = royalties.len() + usize::from(!royalties.contains_key(&owner_id));
+        if let Some(max_len_payout) = max_len_payout {
+            assert!(
+                recipients <= max_len_payout as usize,
+                "Payout has {} recipients, more than max_len_payout {}",
+                recipients,
+                max_len_payout
+            );
+        }
+        let mut payout = HashMap::new();
+        let mut rest = balance;
+        for (account_id, royalty) in royalties {
+            // balance * royalty / ROYALTY_DENOMINATOR, without overflowing for large balances
+            let (denominator, royalty) = (Balance::from(ROYALTY_DENOMINATOR), Balance::from(royalty));
+            let amount = balance / denominator * royalty + balance % denominator * royalty / denominator;
+            rest -= amount;
+            payout.insert(account_id, amount);
+        }
+        *payout.entry(owner_id).or_insert(0) += rest;
+        Payout { payout: payout.into_iter().map(|(account_id, amount)| (account_id, amount.into())).collect() }
+    }
+}
@@ -0,0 +1,68 @@
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::{Config, Contract, ContractExt};
+
+/// The querent's question as sent to `nft_mint`. The plain text never reaches the chain:
+/// `hash` is `sha256(salt ++ question)` and `ciphertext` is the question encrypted by the
+/// client for the token owner, so only they can read it until it is revealed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SealedQuestion {
+    pub hash: Base64VecU8,
+    pub ciphertext: Option<Base64VecU8>,
+}
+
+impl SealedQuestion {
+    pub fn assert_valid(&self, config: &Config) {
+        assert_eq!(self.hash.0.len(), 32, "Question hash must be a 32-byte sha256 hash");
+        if let Some(ciphertext) = &self.ciphertext {
+            config.assert_question_len(&ciphertext.0);
+        }
+    }
+}
+
+/// Question stored with a reading. Once the owner reveals it,
+/// the plain text is kept in `revealed` and the ciphertext is dropped.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Question {
+    pub hash: Base64VecU8,
+    pub ciphertext: Option<Base64VecU8>,
+    pub revealed: Option<String>,
+}
+
+impl From<SealedQuestion> for Question {
+    fn from(sealed: SealedQuestion) -> Self {
+        Self { hash: sealed.hash, ciphertext: sealed.ciphertext, revealed: None }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Publishes the question of `token_id`. Only the token owner can call this, with the
+    /// question and salt it was sealed with. Storage growth is paid from the attached deposit.
+    #[payable]
+    pub fn reveal_question(&mut self, token_id: TokenId, question: String, salt: Base64VecU8) {
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(env::predecessor_account_id(), owner_id, "Only the token owner can reveal its question");
+        let mut stored = self.questions.get(&token_id).expect("Token has no question");
+        assert!(stored.revealed.is_none(), "Question is already revealed");
+        self.config.assert_question_len(question.as_bytes());
+        let initial_storage_usage = env::storage_usage();
+        let preimage = [salt.0, question.clone().into_bytes()].concat();
+        assert_eq!(env::sha256(&preimage), stored.hash.0, "Question does not match its hash");
+        stored.revealed = Some(question);
+        stored.ciphertext = None;
+        self.questions.insert(&token_id, &stored);
+        self.charge_and_refund(0, initial_storage_usage, "reveal");
+    }
+
+    //view the question of a reading, its plain text is only set once the owner revealed it
+    pub fn get_question(&self, token_id: TokenId) -> Option<Question> {
+        self.questions.get(&token_id)
+    }
+}
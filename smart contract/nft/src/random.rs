@@ -0,0 +1,135 @@
+/*!
+Randomness used by card draws.
+NOTES:
+  - Values are taken from the whole 32-byte `env::random_seed()`, one byte at a time. Once the
+    seed is exhausted it is re-hashed with sha256 to extend the stream.
+  - Ranges are sampled with rejection sampling so every value is equally likely, even when the
+    range does not divide 256 (e.g. 22 cards).
+  - The seed is known to the producer of the block it comes from, so draws are paid for in one
+    block and their cards drawn in a later one by the `reveal_draw` callback.
+  - Contract code takes any `RandomSource`, so tests can replay fixed seeds or byte sequences.
+*/
+
+use near_sdk::env;
+
+/// A stream of random bytes.
+pub trait RandomSource {
+    fn next_byte(&mut self) -> u8;
+}
+
+/// Byte stream over a 32-byte seed, normally `env::random_seed()`.
+pub struct SeedStream {
+    seed: [u8; 32],
+    pos: usize,
+}
+
+impl SeedStream {
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self { seed, pos: 0 }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(env::random_seed_array())
+    }
+}
+
+impl RandomSource for SeedStream {
+    fn next_byte(&mut self) -> u8 {
+        if self.pos == self.seed.len() {
+            self.seed = env::sha256_array(&self.seed);
+            self.pos = 0;
+        }
+        let byte = self.seed[self.pos];
+        self.pos += 1;
+        byte
+    }
+}
+
+/// Uniform value in `0..upper_limit`.
+pub fn rand_below<R: RandomSource>(rng: &mut R, upper_limit: u32) -> u32 {
+    assert!(upper_limit > 0, "Upper limit must be greater than 0");
+    let upper = upper_limit as u64;
+    // smallest number of bytes that can represent every value below the limit
+    let mut bytes = 1;
+    while (upper - 1) >> (8 * bytes) > 0 {
+        bytes += 1;
+    }
+    let range = 1u64 << (8 * bytes);
+    // values at or above `zone` would favour the low end of the range, so they are rejected
+    let zone = range - range % upper;
+    loop {
+        let value = (0..bytes).fold(0u64, |acc, _| (acc << 8) | rng.next_byte() as u64);
+        if value < zone {
+            return (value % upper) as u32;
+        }
+    }
+}
+
+/// Fair coin flip.
+pub fn rand_bool<R: RandomSource>(rng: &mut R) -> bool {
+    rand_below(rng, 2) == 1
+}
+
+/// `count` distinct values in `0..upper_limit`, drawn without replacement.
+pub fn rand_distinct<R: RandomSource>(rng: &mut R, upper_limit: u32, count: u32) -> Vec<u32> {
+    assert!(count <= upper_limit, "Cannot draw {} distinct values below {}", count, upper_limit);
+    let mut pool: Vec<u32> = (0..upper_limit).collect();
+    for i in 0..count as usize {
+        let j = i + rand_below(rng, upper_limit - i as u32) as usize;
+        pool.swap(i, j);
+    }
+    pool.truncate(count as usize);
+    pool
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    /// Replays a fixed list of bytes.
+    struct Replay(Vec<u8>);
+
+    impl RandomSource for Replay {
+        fn next_byte(&mut self) -> u8 {
+            self.0.remove(0)
+        }
+    }
+
+    #[test]
+    fn test_rejection_sampling() {
+        // 256 % 22 == 14, so bytes 242..=255 are rejected
+        let mut rng = Replay(vec![255, 242, 241]);
+        assert_eq!(rand_below(&mut rng, 22), 241 % 22);
+        assert!(rng.0.is_empty());
+    }
+
+    #[test]
+    fn test_multi_byte_range() {
+        let mut rng = Replay(vec![0x01, 0x02]);
+        assert_eq!(rand_below(&mut rng, 1000), 0x0102);
+    }
+
+    #[test]
+    fn test_seed_stream_extends_past_seed() {
+        let mut rng = SeedStream::new([7; 32]);
+        for _ in 0..32 {
+            assert_eq!(rng.next_byte(), 7);
+        }
+        assert_eq!(rng.next_byte(), env::sha256_array(&[7; 32])[0]);
+    }
+
+    #[test]
+    fn test_distinct() {
+        let mut rng = SeedStream::new([3; 32]);
+        let mut values = rand_distinct(&mut rng, 22, 22);
+        values.sort_unstable();
+        assert_eq!(values, (0..22).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let first = rand_distinct(&mut SeedStream::new([42; 32]), 78, 10);
+        let second = rand_distinct(&mut SeedStream::new([42; 32]), 78, 10);
+        assert_eq!(first, second);
+    }
+}
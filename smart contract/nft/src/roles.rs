@@ -0,0 +1,116 @@
+/*!
+Access control for privileged methods.
+NOTES:
+  - The contract owner is the NEP-171 `owner_id`. It implicitly holds every role and is the only
+    account that can grant or revoke roles, change the vault, withdraw revenue and upgrade the contract.
+  - Admins manage the config apart from the vault, spreads and decks. Minters can mint a pending draw on behalf of its
+    drawer. Pausers can stop draws, mints and transfers.
+  - Ownership moves in two steps: the owner proposes a new owner with `transfer_ownership`, and
+    the proposed account takes over by calling `accept_ownership`.
+*/
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::{Contract, ContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Minter,
+    Pauser,
+}
+
+impl Role {
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Minter => "minter",
+            Role::Pauser => "pauser",
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grants `role` to `account_id`. Only the contract owner can call this.
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_owner();
+        let mut roles = self.roles.get(&account_id).unwrap_or_default();
+        if !roles.contains(&role) {
+            roles.push(role);
+            self.roles.insert(&account_id, &roles);
+        }
+    }
+
+    /// Revokes `role` from `account_id`. Only the contract owner can call this.
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.assert_owner();
+        let mut roles = self.roles.get(&account_id).unwrap_or_default();
+        roles.retain(|r| *r != role);
+        if roles.is_empty() {
+            self.roles.remove(&account_id);
+        } else {
+            self.roles.insert(&account_id, &roles);
+        }
+    }
+
+    /// Proposes `new_owner_id` as the next contract owner, or withdraws the proposal if omitted.
+    /// Only the contract owner can call this. Nothing changes until the new owner accepts.
+    pub fn transfer_ownership(&mut self, new_owner_id: Option<AccountId>) {
+        self.assert_owner();
+        self.pending_owner_id = new_owner_id;
+    }
+
+    /// Makes the predecessor the contract owner. Only the account proposed with
+    /// `transfer_ownership` can call this.
+    pub fn accept_ownership(&mut self) {
+        let account_id = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_owner_id.as_ref(),
+            Some(&account_id),
+            "Only the proposed owner can accept the ownership"
+        );
+        self.pending_owner_id = None;
+        self.tokens.owner_id = account_id;
+    }
+
+    //view the contract owner
+    pub fn get_owner(&self) -> AccountId {
+        self.tokens.owner_id.clone()
+    }
+
+    //view the proposed owner, if any
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+
+    //view roles granted to an account, the owner holds every role without a grant
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        self.roles.get(&account_id).unwrap_or_default()
+    }
+
+    //view the accounts granted a role
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.roles.iter().filter(|(_, roles)| roles.contains(&role)).map(|(account_id, _)| account_id).collect()
+    }
+
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(env::predecessor_account_id(), self.tokens.owner_id, "Only the contract owner can call this method");
+    }
+
+    pub(crate) fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        account_id == &self.tokens.owner_id || self.roles.get(account_id).is_some_and(|roles| roles.contains(&role))
+    }
+
+    pub(crate) fn assert_role(&self, role: Role) {
+        assert!(
+            self.has_role(&env::predecessor_account_id(), role),
+            "Only the contract owner or an account with the {} role can call this method",
+            role.name()
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, Balance};
+
+use crate::config::Config;
+use crate::roles::Role;
+use crate::deck::{Deck, MAJOR_ARCANA_COUNT};
+use crate::{Contract, ContractExt, Orientation};
+
+pub const SINGLE_SPREAD: &str = "single";
+pub const MAX_SPREAD_ID_LEN: usize = 32;
+pub const MAX_SPREAD_CARDS: usize = 15;
+
+/// A layout of cards drawn together. Each card is drawn without replacement and
+/// placed in one of `positions`, in order.
+/// Without a `price` the spread costs `Config::draw_price` per card.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Spread {
+    pub name: String,
+    pub positions: Vec<String>,
+    pub price: Option<U128>,
+}
+
+impl Spread {
+    fn new(name: &str, positions: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            positions: positions.iter().map(|p| p.to_string()).collect(),
+            price: None,
+        }
+    }
+
+    pub fn price(&self, config: &Config) -> Balance {
+        self.price.map(|p| p.0).unwrap_or(config.draw_price.0 * self.positions.len() as Balance)
+    }
+
+    /// Longest title a reading of this spread drawn from `deck` can get, whatever its token id.
+    /// Matches the titles written by `set_token_metadata`.
+    pub fn max_title_len(&self, deck: &Deck) -> usize {
+        let prefix = format!("Reading#{}: ", u64::MAX).len();
+        if self.positions.len() == 1 {
+            let card_name = deck.cards.iter().map(|card| card.name.len()).max().unwrap_or(0);
+            prefix + card_name + format!(" in {}", Orientation::Reversed.name()).len()
+        } else {
+            prefix + self.name.len()
+        }
+    }
+
+    pub fn assert_valid(&self) {
+        assert!(!self.name.is_empty(), "Spread name must not be empty");
+        assert!(
+            !self.positions.is_empty() && self.positions.len() <= MAX_SPREAD_CARDS,
+            "Spread must have between 1 and {} positions",
+            MAX_SPREAD_CARDS
+        );
+        assert!(self.positions.len() <= MAJOR_ARCANA_COUNT, "Spread has more positions than the smallest deck has cards");
+        assert!(self.positions.iter().all(|p| !p.is_empty()), "Spread positions must not be empty");
+        assert!(!matches!(self.price, Some(U128(0))), "Spread price must be greater than 0");
+    }
+}
+
+/// Spreads every deployment starts with.
+pub fn default_spreads() -> Vec<(String, Spread)> {
+    vec![
+        (SINGLE_SPREAD.to_string(), Spread::new("Single Card", &["Card"])),
+        ("three_card".to_string(), Spread::new("Past, Present, Future", &["Past", "Present", "Future"])),
+        (
+            "celtic_cross".to_string(),
+            Spread::new(
+                "Celtic Cross",
+                &[
+                    "Present",
+                    "Challenge",
+                    "Foundation",
+                    "Recent Past",
+                    "Crown",
+                    "Near Future",
+                    "Self",
+                    "Environment",
+                    "Hopes and Fears",
+                    "Outcome",
+                ],
+            ),
+        ),
+    ]
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Registers or replaces the spread `spread_id`. Only the owner or an admin can call this.
+    /// Pending draws keep the layout they were drawn with.
+    pub fn set_spread(&mut self, spread_id: String, spread: Spread) {
+        self.assert_role(Role::Admin);
+        assert!(
+            !spread_id.is_empty() && spread_id.len() <= MAX_SPREAD_ID_LEN,
+            "Spread id must be between 1 and {} bytes",
+            MAX_SPREAD_ID_LEN
+        );
+        spread.assert_valid();
+        for deck in self.decks.values() {
+            self.config.assert_title_fits(&spread, &deck);
+        }
+        self.spreads.insert(&spread_id, &spread);
+    }
+
+    /// Removes the spread `spread_id`. Only the owner or an admin can call this.
+    pub fn remove_spread(&mut self, spread_id: String) {
+        self.assert_role(Role::Admin);
+        assert!(spread_id != SINGLE_SPREAD, "The single card spread cannot be removed");
+        self.spreads.remove(&spread_id).expect("Spread not found");
+    }
+
+    //view a spread
+    pub fn get_spread(&self, spread_id: String) -> Option<Spread> {
+        self.spreads.get(&spread_id)
+    }
+
+    //view all spreads with their price in yoctoNEAR
+    pub fn get_spreads(&self) -> Vec<(String, Spread, U128)> {
+        self.spreads
+            .iter()
+            .map(|(id, spread)| {
+                let price = spread.price(&self.config).into();
+                (id, spread, price)
+            })
+            .collect()
+    }
+}
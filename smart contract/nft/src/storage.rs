@@ -0,0 +1,130 @@
+use near_contract_standards::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use near_sdk::json_types::U128;
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Balance, Promise, StorageUsage};
+
+use crate::{Contract, ContractExt};
+
+/// Bytes of a `storage_deposits` entry for the longest account id: the record overhead (40),
+/// the key (1 byte prefix, 4 byte length and up to 64 bytes of account id) and the balance (16).
+pub const STORAGE_ACCOUNT_BYTES: StorageUsage = 40 + 1 + 4 + 64 + 16;
+
+/// Storage balances follow NEP-145. The minimum balance pays for the registration itself and is
+/// only returned by `storage_unregister`, the rest pays for the storage of draws, readings,
+/// questions and approvals before any attached deposit is used.
+#[near_bindgen]
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let min_balance = Self::storage_balance_min();
+        let (balance, refund) = match self.storage_deposits.get(&account_id) {
+            Some(balance) if registration_only => (balance, amount),
+            Some(balance) => (balance + amount, 0),
+            None => {
+                assert!(amount >= min_balance, "The attached deposit is less than the minimum storage balance");
+                if registration_only {
+                    (min_balance, amount - min_balance)
+                } else {
+                    (amount, 0)
+                }
+            }
+        };
+        self.storage_deposits.insert(&account_id, &balance);
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        Self::storage_balance(balance)
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let balance = self
+            .storage_deposits
+            .get(&account_id)
+            .unwrap_or_else(|| env::panic_str(&format!("The account {} is not registered", account_id)));
+        let available = balance - Self::storage_balance_min();
+        let amount = amount.map(|a| a.0).unwrap_or(available);
+        assert!(amount <= available, "The amount is greater than the available storage balance");
+        let balance = balance - amount;
+        self.storage_deposits.insert(&account_id, &balance);
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+        Self::storage_balance(balance)
+    }
+
+    /// Returns the whole balance, minimum included. Readings already paid for stay untouched,
+    /// so `force` makes no difference.
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let _ = force;
+        let account_id = env::predecessor_account_id();
+        match self.storage_deposits.remove(&account_id) {
+            Some(balance) => {
+                Promise::new(account_id).transfer(balance);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds { min: Self::storage_balance_min().into(), max: None }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_deposits.get(&account_id).map(Self::storage_balance)
+    }
+}
+
+impl Contract {
+    fn storage_balance_min() -> Balance {
+        env::storage_byte_cost() * Balance::from(STORAGE_ACCOUNT_BYTES)
+    }
+
+    fn storage_balance(balance: Balance) -> StorageBalance {
+        StorageBalance { total: balance.into(), available: (balance - Self::storage_balance_min()).into() }
+    }
+
+    //pays as much of `cost` as the available storage balance of `account_id` allows, returns what is left to pay
+    pub(crate) fn internal_storage_pay(&mut self, account_id: &AccountId, cost: Balance) -> Balance {
+        let balance = match self.storage_deposits.get(account_id) {
+            Some(balance) => balance,
+            None => return cost,
+        };
+        let paid = cost.min(balance - Self::storage_balance_min());
+        if paid > 0 {
+            self.storage_deposits.insert(account_id, &(balance - paid));
+        }
+        cost - paid
+    }
+
+    //pays all of `cost` from the available storage balance of `account_id`, for actions that come without NEAR
+    pub(crate) fn internal_storage_pay_all(&mut self, account_id: &AccountId, cost: Balance, action: &str) {
+        let missing = self.internal_storage_pay(account_id, cost);
+        assert!(missing == 0, "The storage balance of {} cannot cover the {} storage: {:?} more is needed", account_id, action, missing);
+    }
+
+    //credits storage released by `account_id` to their storage balance if they are registered,
+    //otherwise returns the amount to send back to them
+    pub(crate) fn internal_storage_release(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        if self.storage_deposits.contains_key(account_id) {
+            self.internal_storage_credit(account_id, amount);
+            0
+        } else {
+            amount
+        }
+    }
+
+    //credits storage released by `account_id` to their storage balance, if they are registered
+    pub(crate) fn internal_storage_credit(&mut self, account_id: &AccountId, amount: Balance) {
+        if let Some(balance) = self.storage_deposits.get(account_id) {
+            self.storage_deposits.insert(account_id, &(balance + amount));
+        }
+    }
+}
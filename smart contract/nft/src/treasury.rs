@@ -0,0 +1,150 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Balance, Gas, Promise};
+
+use crate::events::FundsWithdrawn;
+use crate::{Contract, ContractExt, GAS_FOR_FT_TRANSFER};
+
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = Gas(5_000_000_000_000);
+
+/// Ledger of the revenue kept by the contract from draws and mints, in NEAR or in one fungible token.
+/// Funds only leave the contract through `withdraw` and `withdraw_ft`, which the owner calls.
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Treasury {
+    pub draw_revenue: Balance,
+    pub mint_revenue: Balance,
+    pub withdrawn: Balance,
+}
+
+impl Treasury {
+    pub fn record_draw(&mut self, amount: Balance) {
+        self.draw_revenue += amount;
+    }
+
+    pub fn record_mint(&mut self, amount: Balance) {
+        self.mint_revenue += amount;
+    }
+
+    pub fn accrued(&self) -> Balance {
+        self.draw_revenue + self.mint_revenue
+    }
+
+    pub fn available(&self) -> Balance {
+        self.accrued() - self.withdrawn
+    }
+
+    //marks `amount` (everything available if omitted) as withdrawn
+    fn withdraw(&mut self, amount: Option<U128>) -> Balance {
+        let available = self.available();
+        let amount = amount.map(|a| a.0).unwrap_or(available);
+        assert!(amount > 0, "Nothing to withdraw");
+        assert!(amount <= available, "Cannot withdraw more than the available {}", available);
+        self.withdrawn += amount;
+        amount
+    }
+
+    pub fn view(&self) -> TreasuryView {
+        TreasuryView {
+            draw_revenue: self.draw_revenue.into(),
+            mint_revenue: self.mint_revenue.into(),
+            accrued: self.accrued().into(),
+            withdrawn: self.withdrawn.into(),
+            available: self.available().into(),
+        }
+    }
+}
+
+/// JSON view of the treasury ledger, returned by `get_treasury`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryView {
+    pub draw_revenue: U128,
+    pub mint_revenue: U128,
+    pub accrued: U128,
+    pub withdrawn: U128,
+    pub available: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sends `amount` of the accrued revenue (everything available if omitted) to the vault.
+    /// Only the contract owner can call this, with exactly 1 yoctoNEAR attached.
+    #[payable]
+    pub fn withdraw(&mut self, amount: Option<U128>) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        let amount = self.treasury.withdraw(amount);
+        Promise::new(self.config.vault_id.clone()).transfer(amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_WITHDRAW)
+                .resolve_withdraw(self.config.vault_id.clone(), amount.into()),
+        )
+    }
+
+    /// Puts `amount` back into the ledger if the transfer to `vault_id` failed.
+    #[private]
+    pub fn resolve_withdraw(&mut self, vault_id: AccountId, amount: U128) -> bool {
+        let success = env::promise_results_count() == 1
+            && matches!(env::promise_result(0), near_sdk::PromiseResult::Successful(_));
+        if success {
+            FundsWithdrawn { vault_id: &vault_id, amount, ft_token_id: None }.emit();
+        } else {
+            self.treasury.withdrawn -= amount.0;
+        }
+        success
+    }
+
+    /// Sends `amount` of the revenue accrued in the fungible token `ft_token_id` (everything available
+    /// if omitted) to the vault, which must be registered with that token.
+    /// Only the contract owner can call this, with exactly 1 yoctoNEAR attached.
+    #[payable]
+    pub fn withdraw_ft(&mut self, ft_token_id: AccountId, amount: Option<U128>) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut treasury = self.ft_treasuries.get(&ft_token_id).unwrap_or_default();
+        let amount = treasury.withdraw(amount);
+        self.ft_treasuries.insert(&ft_token_id, &treasury);
+        ext_ft_core::ext(ft_token_id.clone())
+            .with_attached_deposit(1)
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(self.config.vault_id.clone(), amount.into(), None)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_WITHDRAW)
+                    .resolve_withdraw_ft(ft_token_id, self.config.vault_id.clone(), amount.into()),
+            )
+    }
+
+    /// Puts `amount` of `ft_token_id` back into its ledger if the transfer to `vault_id` failed.
+    #[private]
+    pub fn resolve_withdraw_ft(&mut self, ft_token_id: AccountId, vault_id: AccountId, amount: U128) -> bool {
+        let success = env::promise_results_count() == 1
+            && matches!(env::promise_result(0), near_sdk::PromiseResult::Successful(_));
+        if success {
+            FundsWithdrawn { vault_id: &vault_id, amount, ft_token_id: Some(&ft_token_id) }.emit();
+        } else {
+            self.update_ft_treasury(&ft_token_id, |treasury| treasury.withdrawn -= amount.0);
+        }
+        success
+    }
+
+    //view revenue accrued and withdrawn
+    pub fn get_treasury(&self) -> TreasuryView {
+        self.treasury.view()
+    }
+
+    //view revenue accrued and withdrawn in the fungible token `ft_token_id`
+    pub fn get_ft_treasury(&self, ft_token_id: AccountId) -> TreasuryView {
+        self.ft_treasuries.get(&ft_token_id).unwrap_or_default().view()
+    }
+}
+
+impl Contract {
+    pub(crate) fn update_ft_treasury(&mut self, ft_token_id: &AccountId, update: impl FnOnce(&mut Treasury)) {
+        let mut treasury = self.ft_treasuries.get(ft_token_id).unwrap_or_default();
+        update(&mut treasury);
+        self.ft_treasuries.insert(ft_token_id, &treasury);
+    }
+}
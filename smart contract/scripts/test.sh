@@ -10,6 +10,7 @@ cargo build
 
 cd ../integration-tests/rs
 cargo run --example integration-tests
+cargo run --example upgrade
 
 cd ../ts
 npm run test
\ No newline at end of file